@@ -0,0 +1,86 @@
+use rustupolis::population::district::DistrictZone;
+use rustupolis::procedural_generation::city::{generate_city, CityConfig};
+use std::process::exit;
+use std::str::FromStr;
+use std::{env, fs};
+
+const USAGE: &str = "Usage: citygen [--seed <n>] [--size <buildings>] [--zones core=1,residentials=3,industrials=1,slums=1] [--output <layout.json>]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    exit(1)
+}
+
+/// `core=1,slums=2` into a list of zones with their weight.
+fn parse_zones(arg: &str) -> Vec<(DistrictZone, u32)> {
+    arg.split(',')
+        .map(|part| {
+            let Some((zone, weight)) = part.split_once('=') else {
+                fail(&format!("Invalid zone `{}`, expected <zone>=<weight>", part))
+            };
+            let Ok(zone) = DistrictZone::from_str(zone.trim()) else {
+                fail(&format!("Unknown zone `{}`", zone))
+            };
+            let Ok(weight) = weight.trim().parse() else {
+                fail(&format!("Invalid weight `{}` for {:?}", weight, zone))
+            };
+            (zone, weight)
+        })
+        .collect()
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: Option<&String>) -> T {
+    match value.map(|v| v.parse()) {
+        Some(Ok(n)) => n,
+        _ => fail(&format!("{} needs a number", flag)),
+    }
+}
+
+/// Write a generated starting layout as JSON on stdout or in the `--output` file.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut config = CityConfig::default();
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--seed" => config = config.with_seed(parse_number("--seed", value)),
+            "--size" => config = config.with_size(parse_number("--size", value)),
+            "--zones" => match value {
+                Some(zones) => config = config.with_zones(parse_zones(zones)),
+                None => fail("--zones needs a list of zones"),
+            },
+            "--output" => match value {
+                Some(path) => output = Some(path.clone()),
+                None => fail("--output needs a path"),
+            },
+            other => fail(&format!("Unknown argument `{}`", other)),
+        }
+        i += 2;
+    }
+
+    let layout = match generate_city(&config) {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("The generated city is not valid:\n{}", e);
+            exit(1)
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&layout).unwrap();
+
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, json) {
+                eprintln!("Could not write {}: {}", path, e);
+                exit(1)
+            }
+            eprintln!("Wrote {} buildings and {} roads to {}", layout.buildings.len(), layout.roads.len(), path);
+        }
+        None => println!("{}", json),
+    }
+}
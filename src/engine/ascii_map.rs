@@ -0,0 +1,451 @@
+//! Plain text map format, easier to write by hand and to diff than the JSON layout.
+//!
+//! ```text
+//! ; comments start with a semicolon
+//! @origin 10 5
+//! @districts core residentials
+//! @terrain 42
+//!   AAAA  BBBBBB
+//!   AAAA  BBBBBB
+//! ==============+======
+//!               |
+//! @legend
+//! A = Town hall; district 0; defense wall
+//! B = Future park; empty
+//! C = Mill; function factory
+//! ```
+//!
+//! - `=` is a horizontal road cell, `|` a vertical one and `+` a cell where both cross.
+//!   Stacked rows (or columns) of the same run make a single wider road.
+//! - Any other character is a building, every rectangle of the same character is one building.
+//!   A character can be used for several buildings as long as they don't touch.
+//! - `@origin x y` is optional and gives the layout coordinates of the first map character.
+//! - `@districts` is optional and gives the zone of each district id in order, only a core
+//!   district by default.
+//! - `@terrain seed` is optional and gives the seed of the terrain, flat plain by default.
+//! - `@road <= or |> <x> <y> <length> <width> <class>` gives a road in layout coordinates. When a
+//!   map has these lines they are its only roads and the road cells of the grid are only drawn,
+//!   otherwise every road of the grid is a street.
+//! - The legend is optional: `<label> = <name>` then `; district <id>`, `; empty` (empty lot),
+//!   `; texture <char>`, `; function <function>` or `; defense <defense>`. Buildings without a
+//!   legend are named after their label.
+//!
+//! Ids are generated on import and custom buildings are exported as their bounding box. The export
+//! writes `@road` lines when the grid alone would not give back the same roads.
+
+use crate::engine::drawable::Drawable;
+use crate::engine::layout::{Building, BuildingFunction, BuildingType, Defense, Layout, Road, RoadClass};
+use crate::engine::terrain::Terrain;
+use crate::population::district::DistrictZone;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+const HORIZONTAL_ROAD: char = '=';
+const VERTICAL_ROAD: char = '|';
+const CROSSING: char = '+';
+const DEFAULT_TEXTURE: char = '█';
+
+/// Characters given to buildings on export, in this order.
+const LABELS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789*%&$!?~^<>()[]{}/\\";
+
+#[derive(Debug, Clone, PartialEq)]
+struct LegendEntry {
+    name: String,
+    district_id: usize,
+    b_type: BuildingType,
+    texture: char,
+    function: BuildingFunction,
+    defense: Option<Defense>,
+}
+
+impl LegendEntry {
+    fn from_building(building: &Building) -> Self {
+        LegendEntry {
+            name: building.get_name().to_string(),
+            district_id: building.get_district_id(),
+            b_type: if building.get_building_type() == BuildingType::EmptySpace {
+                BuildingType::EmptySpace
+            } else {
+                BuildingType::Uniform
+            },
+            texture: building.get_texture().unwrap_or(DEFAULT_TEXTURE),
+            function: building.get_function(),
+            defense: building.get_defense(),
+        }
+    }
+
+    fn parse(line: &str, line_number: usize) -> Result<(char, Self), String> {
+        let mut chars = line.chars();
+        let label = chars.next().unwrap();
+        let rest = chars.as_str().trim_start();
+
+        let Some(rest) = rest.strip_prefix('=') else {
+            return Err(format!("line {}: expected `{} = <name>`", line_number, label));
+        };
+
+        let mut parts = rest.split(';').map(|p| p.trim());
+        let mut entry = LegendEntry {
+            name: parts.next().unwrap_or_default().to_string(),
+            district_id: 0,
+            b_type: BuildingType::Uniform,
+            texture: DEFAULT_TEXTURE,
+            function: BuildingFunction::default(),
+            defense: None,
+        };
+
+        for part in parts {
+            let mut words = part.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("district"), Some(id)) => {
+                    entry.district_id = id
+                        .parse()
+                        .map_err(|_| format!("line {}: invalid district `{}`", line_number, id))?
+                }
+                (Some("empty"), None) => entry.b_type = BuildingType::EmptySpace,
+                (Some("texture"), Some(t)) if t.chars().count() == 1 => {
+                    entry.texture = t.chars().next().unwrap()
+                }
+                (Some("function"), Some(f)) => {
+                    entry.function = BuildingFunction::from_str(f)
+                        .map_err(|_| format!("line {}: unknown function `{}`", line_number, f))?
+                }
+                (Some("defense"), Some(d)) => {
+                    entry.defense = Some(
+                        Defense::from_str(d).map_err(|_| format!("line {}: unknown defense `{}`", line_number, d))?,
+                    )
+                }
+                _ => return Err(format!("line {}: unknown option `{}`", line_number, part)),
+            }
+        }
+
+        Ok((label, entry))
+    }
+}
+
+impl std::fmt::Display for LegendEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; district {}", self.name, self.district_id)?;
+        if self.b_type == BuildingType::EmptySpace {
+            write!(f, "; empty")?;
+        }
+        if self.texture != DEFAULT_TEXTURE {
+            write!(f, "; texture {}", self.texture)?;
+        }
+        if self.function != BuildingFunction::default() {
+            write!(f, "; function {}", <&str>::from(self.function))?;
+        }
+        if let Some(defense) = self.defense {
+            write!(f, "; defense {}", <&str>::from(defense))?;
+        }
+        Ok(())
+    }
+}
+
+fn is_road(c: char) -> bool {
+    c == HORIZONTAL_ROAD || c == VERTICAL_ROAD || c == CROSSING
+}
+
+// ----- IMPORT -----
+
+/// Parse a text map into a new layout. The layout is not validated.
+pub fn import_ascii_map(text: &str) -> Result<Layout, String> {
+    let mut origin = (0i16, 0i16);
+    let mut districts = vec![DistrictZone::Core];
+    let mut terrain = None;
+    let mut grid: Vec<Vec<char>> = vec![];
+    let mut legend: HashMap<char, LegendEntry> = HashMap::new();
+    let mut listed_roads: Vec<Road> = vec![];
+    let mut in_legend = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+
+        if line.starts_with(';') {
+            continue;
+        } else if let Some(rest) = line.strip_prefix("@origin") {
+            let coords: Vec<i16> = rest
+                .split_whitespace()
+                .map(|c| c.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("line {}: invalid origin", line_number))?;
+            let [x, y] = coords[..] else {
+                return Err(format!("line {}: expected `@origin <x> <y>`", line_number));
+            };
+            origin = (x, y);
+        } else if let Some(rest) = line.strip_prefix("@terrain") {
+            let seed = rest.trim().parse().map_err(|_| format!("line {}: invalid terrain seed", line_number))?;
+            terrain = Some(Terrain::new(seed));
+        } else if let Some(rest) = line.strip_prefix("@road") {
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let [direction @ ("=" | "|"), x, y, length, width, class] = words[..] else {
+                return Err(format!(
+                    "line {}: expected `@road <= or |> <x> <y> <length> <width> <class>`",
+                    line_number
+                ));
+            };
+            let (Ok(x), Ok(y), Ok(length), Ok(width)) = (x.parse(), y.parse(), length.parse(), width.parse()) else {
+                return Err(format!("line {}: invalid road size", line_number));
+            };
+            let class = RoadClass::from_str(class).map_err(|_| format!("line {}: unknown road class `{}`", line_number, class))?;
+            let horizontal = direction == "=";
+            let mut road = Road::new((x, y), length, width, horizontal, class);
+            road.name = format!("R{}_{}", listed_roads.len() + 1, if horizontal { "HORI" } else { "VERT" });
+            listed_roads.push(road);
+        } else if let Some(rest) = line.strip_prefix("@districts") {
+            districts = rest
+                .split_whitespace()
+                .map(|z| DistrictZone::from_str(z).map_err(|_| format!("line {}: unknown zone `{}`", line_number, z)))
+                .collect::<Result<_, _>>()?;
+        } else if line.trim_end() == "@legend" {
+            in_legend = true;
+        } else if in_legend {
+            if !line.trim().is_empty() {
+                let (label, entry) = LegendEntry::parse(line.trim(), line_number)?;
+                legend.insert(label, entry);
+            }
+        } else {
+            grid.push(line.trim_end().chars().collect());
+        }
+    }
+
+    let at = |x: usize, y: usize| -> char { grid.get(y).and_then(|l| l.get(x)).copied().unwrap_or(' ') };
+    let width = grid.iter().map(|l| l.len()).max().unwrap_or(0);
+    let height = grid.len();
+
+    let mut roads = vec![];
+    let mut horizontal_runs = vec![];
+    for y in 0..height {
+        horizontal_runs.extend(runs((0..width).map(|x| at(x, y)), HORIZONTAL_ROAD).map(|(start, len)| (y, start, len)));
+    }
+    let mut vertical_runs = vec![];
+    for x in 0..width {
+        vertical_runs.extend(runs((0..height).map(|y| at(x, y)), VERTICAL_ROAD).map(|(start, len)| (x, start, len)));
+    }
+
+    for (horizontal, all_runs) in [(true, horizontal_runs), (false, vertical_runs)] {
+        for (across, along, length, thickness) in stack_runs(all_runs) {
+            let (x, y) = if horizontal { (along, across) } else { (across, along) };
+            if length > u8::MAX as usize || thickness > u8::MAX as usize {
+                return Err(format!("road at {}, {} is too big", x, y));
+            }
+
+            let mut road = Road::new(
+                (origin.0 + x as i16, origin.1 + y as i16),
+                length as u8,
+                thickness as u8,
+                horizontal,
+                RoadClass::Street,
+            );
+            road.name = format!("R{}_{}", roads.len() + 1, if horizontal { "HORI" } else { "VERT" });
+            roads.push(road);
+        }
+    }
+
+    if !listed_roads.is_empty() {
+        roads = listed_roads;
+    }
+
+    let mut buildings = vec![];
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    for y in 0..height {
+        for x in 0..width {
+            let label = at(x, y);
+            if label == ' ' || is_road(label) || seen.contains(&(x, y)) {
+                continue;
+            }
+
+            // the top left cell of a rectangle is the first one we meet
+            let right = (x..width).take_while(|&x2| at(x2, y) == label).last().unwrap();
+            let bottom = (y..height).take_while(|&y2| at(x, y2) == label).last().unwrap();
+            let (w, h) = (right - x + 1, bottom - y + 1);
+
+            for y2 in y..=bottom {
+                for x2 in x..=right {
+                    if at(x2, y2) != label || !seen.insert((x2, y2)) {
+                        return Err(format!("building `{}` at {}, {} is not a rectangle", label, x, y));
+                    }
+                }
+            }
+            let touches_outside = (x..=right).any(|x2| at(x2, bottom + 1) == label)
+                || (y..=bottom).any(|y2| at(right + 1, y2) == label);
+            if touches_outside || w > u8::MAX as usize || h > u8::MAX as usize {
+                return Err(format!("building `{}` at {}, {} is not a rectangle", label, x, y));
+            }
+
+            let entry = legend.get(&label).cloned().unwrap_or(LegendEntry {
+                name: format!("Building {}", label),
+                district_id: 0,
+                b_type: BuildingType::Uniform,
+                texture: DEFAULT_TEXTURE,
+                function: BuildingFunction::default(),
+                defense: None,
+            });
+
+            buildings.push(
+                Building::new_at(origin.0 + x as i16, origin.1 + y as i16, w as u8, h as u8)
+                    .with_name(&entry.name)
+                    .with_district(entry.district_id)
+                    .with_type(entry.b_type)
+                    .with_texture(entry.texture)
+                    .with_function(entry.function)
+                    .with_defense(entry.defense),
+            );
+        }
+    }
+
+    let mut layout = Layout::new(buildings, roads);
+    layout.districts = districts;
+    layout.terrain = terrain;
+    Ok(layout)
+}
+
+/// Return (start, length) of every run of `road` or `CROSSING` cells.
+fn runs(cells: impl Iterator<Item = char>, road: char) -> impl Iterator<Item = (usize, usize)> {
+    let mut res = vec![];
+    let mut start = None;
+
+    for (i, c) in cells.chain(std::iter::once(' ')).enumerate() {
+        match (start, c == road || c == CROSSING) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                res.push((s, i - s));
+                start = None
+            }
+            _ => (),
+        }
+    }
+
+    res.into_iter()
+}
+
+/// Merge identical runs on consecutive lines into (first line, start, length, thickness).
+fn stack_runs(runs: Vec<(usize, usize, usize)>) -> Vec<(usize, usize, usize, usize)> {
+    let mut stacked: Vec<(usize, usize, usize, usize)> = vec![];
+
+    for (line, start, length) in runs {
+        if let Some(road) = stacked
+            .iter_mut()
+            .find(|(first, s, l, thickness)| *s == start && *l == length && first + thickness == line)
+        {
+            road.3 += 1;
+        } else {
+            stacked.push((line, start, length, 1));
+        }
+    }
+
+    stacked
+}
+
+// ----- EXPORT -----
+
+/// Dump the layout as a text map with its legend, see the module documentation for the format.
+pub fn export_ascii_map(layout: &Layout) -> Result<String, String> {
+    let Some((min_x, min_y, max_x, max_y)) = layout.bounds() else {
+        return Ok(String::new());
+    };
+
+    let mut grid = vec![vec![' '; (max_x - min_x) as usize]; (max_y - min_y) as usize];
+    let mut set = |x: i16, y: i16, c: char| {
+        let cell = &mut grid[(y - min_y) as usize][(x - min_x) as usize];
+        *cell = match (*cell, c) {
+            (HORIZONTAL_ROAD, VERTICAL_ROAD) | (VERTICAL_ROAD, HORIZONTAL_ROAD) | (CROSSING, _) => CROSSING,
+            _ => c,
+        };
+    };
+
+    for road in &layout.roads {
+        let c = if road.is_horizontal() { HORIZONTAL_ROAD } else { VERTICAL_ROAD };
+        for (x, y) in road.get_area() {
+            set(x, y, c);
+        }
+    }
+
+    // Same order as the import so a round trip gives the same labels
+    let mut buildings: Vec<&Building> = layout.buildings.iter().collect();
+    buildings.sort_by_key(|b| (b.y(), b.x()));
+
+    // Buildings with the same legend can share a label if they don't touch
+    let mut labels: Vec<(char, LegendEntry, Vec<&Building>)> = vec![];
+    let mut free_labels = LABELS.chars();
+
+    for bldg in buildings {
+        let entry = LegendEntry::from_building(bldg);
+        let touches = |other: &&Building| {
+            bldg.x() <= other.x() + other.width() as i16
+                && other.x() <= bldg.x() + bldg.width() as i16
+                && bldg.y() <= other.y() + other.height() as i16
+                && other.y() <= bldg.y() + bldg.height() as i16
+        };
+
+        let index = match labels
+            .iter()
+            .position(|(_, e, users)| *e == entry && !users.iter().any(touches))
+        {
+            Some(i) => i,
+            None => {
+                let Some(label) = free_labels.next() else {
+                    return Err("too many different buildings to give them a label".to_string());
+                };
+                labels.push((label, entry, vec![]));
+                labels.len() - 1
+            }
+        };
+
+        labels[index].2.push(bldg);
+        for y in bldg.y()..(bldg.y() + bldg.height() as i16) {
+            for x in bldg.x()..(bldg.x() + bldg.width() as i16) {
+                set(x, y, labels[index].0);
+            }
+        }
+    }
+
+    let origin = format!("@origin {} {}\n", min_x, min_y);
+    let mut map = String::new();
+    for line in grid {
+        map.push_str(line.iter().collect::<String>().trim_end());
+        map.push('\n');
+    }
+
+    let mut out = origin.clone();
+    if layout.districts != [DistrictZone::Core] {
+        let zones: Vec<String> = layout.districts.iter().map(|z| z.to_string()).collect();
+        out.push_str(&format!("@districts {}\n", zones.join(" ")));
+    }
+    if let Some(terrain) = &layout.terrain {
+        out.push_str(&format!("@terrain {}\n", terrain.seed));
+    }
+    if !grid_keeps_roads(layout, &(origin + &map))? {
+        for road in &layout.roads {
+            let direction = if road.is_horizontal() { HORIZONTAL_ROAD } else { VERTICAL_ROAD };
+            out.push_str(&format!(
+                "@road {} {} {} {} {} {}\n",
+                direction,
+                road.start_x,
+                road.start_y,
+                road.length,
+                road.width,
+                <&str>::from(road.get_class())
+            ));
+        }
+    }
+    out.push_str(&map);
+
+    out.push_str("@legend\n");
+    for (label, entry, _) in labels {
+        out.push_str(&format!("{} = {}\n", label, entry));
+    }
+
+    Ok(out)
+}
+
+/// Whether importing `map` without `@road` lines gives back the roads of the layout.
+fn grid_keeps_roads(layout: &Layout, map: &str) -> Result<bool, String> {
+    let key = |r: &Road| (r.is_horizontal(), r.start_x, r.start_y, r.length, r.width, r.get_class());
+
+    let mut original: Vec<_> = layout.roads.iter().map(key).collect();
+    let mut imported: Vec<_> = import_ascii_map(map)?.roads.iter().map(key).collect();
+    original.sort_unstable_by_key(|k| (k.0, k.1, k.2, k.3, k.4));
+    imported.sort_unstable_by_key(|k| (k.0, k.1, k.2, k.3, k.4));
+
+    Ok(original == imported)
+}
@@ -1,34 +1,56 @@
 use std::cmp::min;
 // use std::any::type_name;
 // use std::cmp::PartialEq;
-use crate::engine::drawable::DynDrawable;
+use crate::engine::drawable::{DrawableType, DynDrawable};
 use crate::engine::keybinds::Tty;
 use crate::engine::viewport::{background, Viewport};
 use crate::threads::sidebar::SideBarMessage;
-use crate::ui::colors::A_UI_BLACK_LIGHT_COLOR;
+use crate::ui::colors::{A_ALERT_COLOR, A_LIGHT_COLOR, A_UI_BLACK_LIGHT_COLOR};
 use log::{debug, trace};
 use std::io::Write;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, RwLock};
 use termion::{cursor, terminal_size};
-use crate::engine::layout::{Layout};
+use crate::engine::layout::{BuildingFunction, Layout};
+use crate::engine::terrain::Terrain;
 use crate::population::Population;
+use crate::simulation::balance::Balance;
+use crate::simulation::events::Events;
+use crate::simulation::monsters::Monsters;
+use crate::simulation::services::{ServiceKind, Services};
+use crate::simulation::traffic::Traffic;
+use crate::simulation::treasury::Treasury;
 // use crate::threads::engine_loop::Selection;
 
-pub type LockableEngine<'a> = Arc<RwLock<Engine<'a>>>;
+pub type LockableEngine = Arc<RwLock<Engine>>;
 
-pub struct Engine<'a> {
+pub struct Engine {
     pub viewport: Viewport,
     pub side_bar_tx: Sender<SideBarMessage>,
     pub background: String,
     pub stdout: Arc<Tty>,
-    pub layout: Layout<'a>,
+    pub layout: Layout,
     pub population: Population,
-    pub drawables: Vec<Box<DynDrawable>>
+    /// Coefficients of the simulation, see `Balance`
+    pub balance: Balance,
+    pub drawables: Vec<Box<DynDrawable>>,
+    pub traffic: Traffic,
+    pub monsters: Monsters,
+    pub services: Services,
+    pub treasury: Treasury,
+    pub events: Events,
+    /// Colour roads by their load instead of their class
+    pub show_traffic: bool,
+    /// Colour buildings by their coverage of this service instead of their district
+    pub show_coverage: Option<ServiceKind>,
+    /// Function given to the buildings built on empty lots
+    pub build_function: BuildingFunction,
+    /// Cells of the last route found by the GPS, drawn over the roads
+    pub route: Vec<(i16, i16)>
 }
 
 
-impl Engine<'_> {
+impl Engine {
     pub fn register_drawable(&mut self, drawable: Box<DynDrawable>) {
         self.drawables.push(drawable)
     }
@@ -62,6 +84,19 @@ impl Engine<'_> {
             let mut coordinates = self.viewport.get_output_coordinates(d);
             trace!("blit at: {:?}", coordinates);
 
+            let color = match d.d_type() {
+                DrawableType::Road if self.show_traffic => match self.layout.roads.iter().find(|r| r.id == d.id()) {
+                    Some(road) => self.traffic.color(road),
+                    None => d.color(&self.population),
+                },
+                DrawableType::Building if self.show_coverage.is_some() => {
+                    self.show_coverage.map_or(A_ALERT_COLOR, |kind| self.services.color(&d.id(), kind))
+                }
+                // can't be reached by road
+                DrawableType::Building if self.layout.graph.is_isolated(&d.id()) => A_ALERT_COLOR,
+                _ => d.color(&self.population),
+            };
+
             for line in &d.shape().lines().collect::<Vec<&str>>()
                 [coordinates.crop_top..min((d.height() as usize - coordinates.crop_bottom), d.shape().lines().count())]
             {
@@ -69,7 +104,7 @@ impl Engine<'_> {
                     self.stdout.lock(),
                     "{}{}",
                     cursor::Goto(coordinates.x, coordinates.y),
-                    d.color(&self.population).paint(
+                    color.paint(
                         line.chars().collect::<Vec<char>>()
                             [coordinates.crop_left..(d.width() as usize - coordinates.crop_right)]
                             .iter()
@@ -80,6 +115,18 @@ impl Engine<'_> {
             }
         }
 
+        for &(x, y) in &self.route {
+            if let Some((x, y)) = self.viewport.get_output_point(x, y) {
+                let _ = write!(self.stdout.lock(), "{}{}", cursor::Goto(x, y), A_LIGHT_COLOR.bold().paint("#"));
+            }
+        }
+
+        for monster in self.monsters.get_active() {
+            if let Some((x, y)) = self.viewport.get_output_point(monster.x, monster.y) {
+                let _ = write!(self.stdout.lock(), "{}{}", cursor::Goto(x, y), A_ALERT_COLOR.bold().paint("M"));
+            }
+        }
+
         self.stdout.lock().flush().unwrap()
     }
 
@@ -93,6 +140,10 @@ impl Engine<'_> {
     }
 
     fn clear_viewport(&self) {
+        if let Some(terrain) = &self.layout.terrain {
+            return self.draw_terrain(terrain);
+        }
+
         let bg_lines = self.background.lines().collect::<Vec<&str>>();
         trace!("{}", bg_lines[0].chars().count());
         for y in self.viewport.output_y..(self.viewport.output_y + self.viewport.height) {
@@ -105,12 +156,46 @@ impl Engine<'_> {
 
         self.stdout.lock().flush().unwrap()
     }
+
+    /// Same as the random background but it follows the viewport.
+    fn draw_terrain(&self, terrain: &Terrain) {
+        for y in self.viewport.output_y..(self.viewport.output_y + self.viewport.height) {
+            let (virtual_x, virtual_y) = self.viewport.get_virtual_coordinates(self.viewport.output_x, y);
+
+            // one escape code for each run of the same colour
+            let mut line = String::new();
+            let mut run = String::new();
+            let mut run_color: Option<ansi_term::Color> = None;
+            for dx in 0..self.viewport.width as i16 {
+                let (glyph, color) = terrain.glyph_at(virtual_x + dx, virtual_y);
+                if run_color.is_some_and(|c| c != color) {
+                    line.push_str(&run_color.unwrap().paint(run.as_str()).to_string());
+                    run.clear();
+                }
+                run_color = Some(color);
+                run.push(glyph);
+            }
+            if let Some(color) = run_color {
+                line.push_str(&color.paint(run.as_str()).to_string());
+            }
+
+            print!("{}{}", cursor::Goto(self.viewport.output_x, y), line)
+        }
+
+        self.stdout.lock().flush().unwrap()
+    }
 }
 
-impl<'a> Engine<'a> {
-    pub fn new(viewport: Viewport, stdout: Arc<Tty>, chan: Sender<SideBarMessage>, mut layout: Layout<'a>) -> Self {
+impl Engine {
+    pub fn new(
+        viewport: Viewport,
+        stdout: Arc<Tty>,
+        chan: Sender<SideBarMessage>,
+        mut layout: Layout,
+        balance: Balance,
+    ) -> Self {
         trace!("{:?}", terminal_size());
-        let pop = Population::new(&mut layout);
+        let pop = Population::new(&mut layout, balance.districts.clone());
         Engine {
             viewport,
             stdout,
@@ -118,6 +203,16 @@ impl<'a> Engine<'a> {
             side_bar_tx: chan,
             drawables: vec![],
             population: pop,
+            balance,
+            traffic: Traffic::default(),
+            monsters: Monsters::default(),
+            services: Services::default(),
+            treasury: Treasury::default(),
+            events: Events::default(),
+            show_traffic: false,
+            show_coverage: None,
+            build_function: BuildingFunction::Housing,
+            route: vec![],
             background: { background(viewport.output_y, viewport.width, viewport.height) },
         }
     }
@@ -1,5 +1,9 @@
 use std::{env, fs};
+use crate::engine::ascii_map::export_ascii_map;
 use crate::engine::core::{Engine};
+use crate::engine::layout::LayoutId;
+use crate::engine::snapshot::{export_html, export_svg};
+use crate::simulation::services::ServiceKind;
 use crate::utils::interruptible_sleep::InterruptibleSleep;
 use log::{debug, trace};
 use std::io::{stdin, Stdout};
@@ -13,8 +17,8 @@ use termion::event::{Event, Key, MouseButton, MouseEvent};
 use termion::input::{MouseTerminal, TermRead};
 use termion::raw::RawTerminal;
 use crate::threads::sidebar::SideBarMessage;
-use crate::ui::sidebar::SyncDisplay;
-use crate::utils::{send_to_side_bar_write};
+use crate::ui::sidebar::{LogColor, LogType, SyncDisplay};
+use crate::utils::{send_to_side_bar_read, send_to_side_bar_write};
 
 pub type Tty = MouseTerminal<RawTerminal<Stdout>>;
 
@@ -33,7 +37,7 @@ pub static RUNNING: AtomicBool = AtomicBool::new(true);
 
 impl<'scope> KeyBindListener<'scope> {
     pub fn new<'env>(
-        s: &'scope Scope<'scope, 'env>, e: Arc<RwLock<Engine<'env>>>,
+        s: &'scope Scope<'scope, 'env>, e: Arc<RwLock<Engine>>,
         click_subscribers: Vec<Sender<(i16, i16, (Option<MouseButton>, Option<Key>))>>,
         keys_subscribers: Vec<Sender<Key>>,
         sidebar: Sender<SideBarMessage>
@@ -71,11 +75,54 @@ impl<'scope> KeyBindListener<'scope> {
                             let _ = sender.send((0,0, (None, Some(Key::Char('\n')))));
                         }
                     },
+                    Event::Key(k @ (Key::Char('x') | Key::Char('m') | Key::Char('r') | Key::Char('g') | Key::Char('f') | Key::Char('e') | Key::Delete)) => {
+                        for sender in &clicks {
+                            let _ = sender.send((0,0, (None, Some(*k))));
+                        }
+                    },
                     Event::Key(Key::Esc) => {
                         for sender in &clicks {
                             let _ = sender.send((0,0, (None, Some(Key::Esc))));
                         }
                     },
+                    Event::Key(Key::Char('t')) => {
+                        if let Ok(ref mut engine) = cop.write() {
+                            engine.show_traffic = !engine.show_traffic;
+                            engine.refresh();
+                        }
+                    },
+                    Event::Key(Key::Char('v')) => {
+                        if let Ok(ref mut engine) = cop.write() {
+                            engine.show_coverage = ServiceKind::next(engine.show_coverage);
+                            if let Some(kind) = engine.show_coverage {
+                                let buildings: Vec<LayoutId> = engine.layout.buildings.iter().map(|b| b.id).collect();
+                                let gaps = engine.services.count_gaps(&buildings, kind);
+                                let message = format!("Coverage of {}: {} buildings out of reach", kind, gaps);
+                                send_to_side_bar_write(engine, SideBarMessage::Single(Box::new(message), LogType::Info, LogColor::Normal));
+                            }
+                            engine.refresh();
+                        }
+                    },
+                    Event::Key(Key::Char('$')) => {
+                        if let Ok(ref mut engine) = cop.write() {
+                            engine.treasury.tax_law = engine.treasury.tax_law.next();
+                            let message = format!("Taxes are now {}", engine.treasury.tax_law);
+                            send_to_side_bar_write(engine, SideBarMessage::Single(Box::new(message), LogType::City, LogColor::Normal));
+                        }
+                    },
+                    Event::Key(Key::Char('b')) => {
+                        if let Ok(ref mut engine) = cop.write() {
+                            engine.build_function = engine.build_function.next();
+                            let message = format!("Empty lots will be built as {}", engine.build_function);
+                            send_to_side_bar_write(engine, SideBarMessage::Single(Box::new(message), LogType::Info, LogColor::Normal));
+                        }
+                    },
+                    Event::Key(Key::Char('c')) => {
+                        if let Ok(ref engine) = cop.read() {
+                            let lines = engine.layout.graph.connectivity_report().describe(&engine.layout);
+                            send_to_side_bar_read(engine, SideBarMessage::CustomInfos(Box::new("Road network"), lines.into_iter().map(|l| Box::new(l) as Box<SyncDisplay>).collect()));
+                        }
+                    },
                     Event::Key(Key::Ctrl('s')) => {
                         match cop.read() {
                             Ok(ref engine) => {
@@ -93,6 +140,27 @@ impl<'scope> KeyBindListener<'scope> {
                             _ => {}
                         }
                     },
+                    Event::Key(Key::Ctrl('e')) => {
+                        if let Ok(ref engine) = cop.read() {
+                            let time = chrono::offset::Local::now();
+
+                            let name = save_dir.join(time.format("layout-%Y-%m-%d-%H-%M-%S.map").to_string());
+
+                            if let Ok(map) = export_ascii_map(&engine.layout) {
+                                let _ = fs::write(name, map);
+                            }
+                        }
+                    },
+                    Event::Key(Key::Ctrl('p')) => {
+                        if let Ok(ref engine) = cop.read() {
+                            let time = chrono::offset::Local::now();
+
+                            let name = save_dir.join(time.format("snapshot-%Y-%m-%d-%H-%M-%S").to_string());
+
+                            let _ = fs::write(name.with_extension("svg"), export_svg(&engine.layout, &engine.population));
+                            let _ = fs::write(name.with_extension("html"), export_html(&engine.layout, &engine.population));
+                        }
+                    },
                     Event::Mouse(mouse_event) => match mouse_event {
                         MouseEvent::Press(click_type, x, y) => {
                             debug!("Mouse click at x: {} y: {} | {:?}", x, y, click_type);
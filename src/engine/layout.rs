@@ -1,15 +1,20 @@
 use super::core::Engine;
 use super::drawable::DrawableType;
+use super::migrations::{migrate, LayoutError, LAYOUT_FORMAT_VERSION};
 use super::{drawable::Drawable, keybinds::Clickable};
+use super::terrain::{Terrain, TerrainKind};
+use crate::population::district::DistrictZone;
+use crate::population::people::WorkLethality;
 use crate::population::Population;
-use crate::roads::road_graph::{Graph, Rect};
+use crate::procedural_generation::{is_area_free_except, AreaPartition};
+use crate::roads::road_graph::Graph;
+use crate::roads::routing::{find_route, Route};
 use crate::threads::engine_loop::Selection;
-use crate::utils::intersections::intersection;
 use crate::{lock_read, lock_unlock, population::people::BasePeopleInfo, ui::colors::*};
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine as b64Engine;
 use log::{debug, trace};
-use rand::{rng, Fill};
+use rand::{rng, Rng};
 use serde::de::Error;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::array::IntoIter;
@@ -17,12 +22,18 @@ use std::cmp::PartialEq;
 use std::fmt::Display;
 use std::fmt::{Debug, Formatter};
 use std::slice::Iter;
+use std::collections::HashMap;
+use strum_macros::{EnumString, IntoStaticStr};
 
 pub const LAYOUT_ID_LENGTH: usize = 12;
 pub const TERMINAL_RATIO: u8 = 2;
 pub const ROAD_WIDTH: i16 = 2;
+/// Gap a road drawn in the editor can jump to reach another road
+pub const ROAD_SNAP_DISTANCE: i16 = 2;
+/// Price of one cell of a building drawn in the editor
+pub const BUILDING_CELL_COST: u32 = 10;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct LayoutId {
     value: [u8; LAYOUT_ID_LENGTH],
 }
@@ -37,10 +48,13 @@ impl LayoutId {
     }
 
     pub fn random() -> Self {
-        let mut rng = rng();
+        Self::random_from(&mut rng())
+    }
 
+    /// Use a seeded rng to get the same ids every time.
+    pub fn random_from(rng: &mut impl Rng) -> Self {
         let mut x = [0u8; LAYOUT_ID_LENGTH];
-        x.fill(&mut rng);
+        rng.fill(&mut x);
         LayoutId { value: x }
     }
 }
@@ -59,7 +73,7 @@ impl<'de> Deserialize<'de> for LayoutId {
     where
         D: Deserializer<'de>,
     {
-        let s: &str = de::Deserialize::deserialize(deserializer)?;
+        let s: String = de::Deserialize::deserialize(deserializer)?;
         let res = BASE64_STANDARD.decode(s);
 
         if let Err(_) = res {
@@ -117,6 +131,148 @@ impl Display for BuildingType {
     }
 }
 
+/// Protection against monsters, see `simulation::monsters`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, EnumString, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Defense {
+    /// Repels most attacks on the buildings around it
+    Guard,
+    /// Monsters can't walk next to it
+    Wall,
+}
+
+impl Defense {
+    /// None -> Guard -> Wall -> None
+    pub fn next(defense: Option<Defense>) -> Option<Defense> {
+        match defense {
+            None => Some(Defense::Guard),
+            Some(Defense::Guard) => Some(Defense::Wall),
+            Some(Defense::Wall) => None,
+        }
+    }
+}
+
+impl Display for Defense {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Defense::Guard => write!(f, "guard post"),
+            Defense::Wall => write!(f, "wall"),
+        }
+    }
+}
+
+/// What the building is used for, the rules of each function are in `FunctionRules`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default, EnumString, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BuildingFunction {
+    #[default]
+    Housing,
+    Factory,
+    Farm,
+    Hospital,
+    School,
+    Police,
+}
+
+/// Everything the simulation needs to know about a building function, all the numbers are per
+/// cell of the building.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FunctionRules {
+    /// People that can live in it before it is overcrowded
+    pub residents: usize,
+    pub jobs: usize,
+    /// Multiplier of the food grown on the terrain (see `TerrainKind::food_production`)
+    pub food: f32,
+    pub work_risk: WorkLethality,
+    /// Paid by the city each month
+    pub upkeep: u32,
+    /// People that can be treated, taught or watched over
+    pub patients: usize,
+    pub pupils: usize,
+    pub patrols: usize,
+    /// Travel time by road to the buildings it serves, same unit as `cell_cost`
+    pub range: u32,
+}
+
+impl BuildingFunction {
+    pub const ALL: [BuildingFunction; 6] = [
+        BuildingFunction::Housing,
+        BuildingFunction::Factory,
+        BuildingFunction::Farm,
+        BuildingFunction::Hospital,
+        BuildingFunction::School,
+        BuildingFunction::Police,
+    ];
+
+    pub fn rules(&self) -> FunctionRules {
+        let none = FunctionRules {
+            residents: 0,
+            jobs: 0,
+            food: 0.0,
+            work_risk: WorkLethality::SafeJob,
+            upkeep: 0,
+            patients: 0,
+            pupils: 0,
+            patrols: 0,
+            range: 0,
+        };
+
+        match self {
+            // shops and workshops on the ground floor
+            BuildingFunction::Housing => FunctionRules { residents: 3, jobs: 1, food: 1.0, ..none },
+            BuildingFunction::Factory => {
+                FunctionRules { residents: 1, jobs: 4, work_risk: WorkLethality::HighRisks, ..none }
+            }
+            BuildingFunction::Farm => {
+                FunctionRules { residents: 1, jobs: 2, food: 4.0, work_risk: WorkLethality::MediumRisks, ..none }
+            }
+            BuildingFunction::Hospital => FunctionRules { jobs: 2, upkeep: 3, patients: 10, range: 900, ..none },
+            BuildingFunction::School => FunctionRules { jobs: 1, upkeep: 2, pupils: 10, range: 600, ..none },
+            BuildingFunction::Police => FunctionRules {
+                jobs: 1,
+                upkeep: 2,
+                patrols: 15,
+                work_risk: WorkLethality::MediumRisks,
+                range: 700,
+                ..none
+            },
+        }
+    }
+
+    /// Housing -> Factory -> Farm -> Hospital -> School -> Police -> Housing
+    pub fn next(&self) -> BuildingFunction {
+        let index = Self::ALL.iter().position(|f| f == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Name given to the buildings built on empty lots
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            BuildingFunction::Housing => "Housing",
+            BuildingFunction::Factory => "Factory",
+            BuildingFunction::Farm => "Farm",
+            BuildingFunction::Hospital => "Hospital",
+            BuildingFunction::School => "School",
+            BuildingFunction::Police => "Police Station",
+        }
+    }
+}
+
+impl Display for BuildingFunction {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            BuildingFunction::Housing => write!(f, "housing"),
+            BuildingFunction::Factory => write!(f, "factory"),
+            BuildingFunction::Farm => write!(f, "farm"),
+            BuildingFunction::Hospital => write!(f, "hospital"),
+            BuildingFunction::School => write!(f, "school"),
+            BuildingFunction::Police => write!(f, "police"),
+        }
+    }
+}
+
 // ----- BUILDINGS -----
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -131,28 +287,39 @@ pub struct Building {
     height: Option<u8>,
     texture: Option<char>,
     content: Option<Vec<String>>,
+    defense: Option<Defense>,
+    function: BuildingFunction,
 }
 
 impl Building {
+    /// Alive people living here, read from the occupancy of the district (see `PopulationDistrict::get_occupants`)
     pub fn get_num_people_in_building(&self, population: &Population) -> usize {
-        if let Some(pop) = population.get_district(self.district_id) {
-            pop.peoples
-                .iter()
-                .filter(|people| {
-                    if let Some(uuid) = people.get_building_uuid() {
-                        *uuid == self.id
-                    } else {
-                        false
-                    }
-                })
-                .count()
-        } else {
-            0
-        }
+        population
+            .get_district(self.district_id)
+            .map_or(0, |district| district.get_occupants(&self.id))
     }
 
     pub fn is_overcrowded(&self, population: &Population) -> bool {
-        self.get_num_people_in_building(population) > self.get_area().len() * 3
+        self.get_num_people_in_building(population) > self.get_housing_capacity()
+    }
+
+    pub fn has_room(&self, population: &Population) -> bool {
+        self.get_num_people_in_building(population) < self.get_housing_capacity()
+    }
+
+    /// Nobody lives on an empty lot
+    pub fn get_housing_capacity(&self) -> usize {
+        match self.b_type {
+            BuildingType::EmptySpace => 0,
+            _ => self.get_area().len() * self.function.rules().residents,
+        }
+    }
+
+    pub fn get_job_capacity(&self) -> usize {
+        match self.b_type {
+            BuildingType::EmptySpace => 0,
+            _ => self.get_area().len() * self.function.rules().jobs,
+        }
     }
 
     pub fn get_building_uuid(&self) -> LayoutId {
@@ -211,9 +378,69 @@ impl Building {
             height: Some(height),
             texture: Some('█'),
             content: None,
+            defense: None,
+            function: BuildingFunction::Housing,
+        }
+    }
+
+    pub fn with_name(self, name: &str) -> Self {
+        Building {
+            name: name.to_string(),
+            ..self
+        }
+    }
+
+    pub fn with_district(self, district_id: usize) -> Self {
+        Building { district_id, ..self }
+    }
+
+    /// Custom buildings need a content, use this for uniform buildings or empty lots.
+    pub fn with_type(self, b_type: BuildingType) -> Self {
+        Building { b_type, ..self }
+    }
+
+    pub fn with_texture(self, texture: char) -> Self {
+        Building {
+            texture: Some(texture),
+            ..self
+        }
+    }
+
+    /// Turns the building into a custom one, all lines need to have the same width.
+    pub fn with_content(self, content: Vec<String>) -> Self {
+        Building {
+            b_type: BuildingType::Custom,
+            width: None,
+            height: None,
+            content: Some(content),
+            ..self
         }
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_texture(&self) -> Option<char> {
+        self.texture
+    }
+
+    pub fn get_defense(&self) -> Option<Defense> {
+        self.defense
+    }
+
+    pub fn with_defense(self, defense: Option<Defense>) -> Self {
+        Building { defense, ..self }
+    }
+
+    pub fn get_function(&self) -> BuildingFunction {
+        self.function
+    }
+
+    pub fn with_function(self, function: BuildingFunction) -> Self {
+        Building { function, ..self }
+    }
+
     pub fn get_area(&self) -> Vec<(i16, i16)> {
         let mut tiles = Vec::new();
 
@@ -243,7 +470,7 @@ impl Building {
 
 impl Clickable for Building {
     fn infos(&self, engine: &Engine) -> Option<Vec<String>> {
-        let x = Some(vec![
+        let mut lines = vec![
             String::from(format!("Name: {}", self.name)),
             String::from(format!("Position: {}, {}", self.pos_x, self.pos_y)),
             String::from(format!(
@@ -254,9 +481,28 @@ impl Clickable for Building {
                 "Population: {}",
                 self.get_num_people_in_building(&engine.population)
             )),
-            String::from(" ".to_string()), // act as a newline
-        ]);
-        x
+            format!(
+                "Function: {} ({} residents, {} jobs)",
+                self.function,
+                self.get_housing_capacity(),
+                self.get_job_capacity()
+            ),
+            format!("Defense: {}", self.defense.map_or("none".to_string(), |d| d.to_string())),
+        ];
+
+        // DNA of the witnesses living here, phenotype then genotype
+        if let Some(district) = engine.population.get_district(self.district_id) {
+            for alive in district.peoples.iter().filter_map(|p| p.as_alive()) {
+                if let (Some(name), Some(home)) = (alive.get_witness_name(), alive.building_uuid) {
+                    if home == self.id {
+                        lines.push(format!("{}: {:?}", name, alive.dna));
+                    }
+                }
+            }
+        }
+
+        lines.push(String::from(" ".to_string())); // act as a newline
+        Some(lines)
     }
 }
 
@@ -352,13 +598,14 @@ impl Drawable for Building {
         match &self.b_type {
             s if s == &BuildingType::EmptySpace => A_SAND_COLOR,
             _ => {
-                if self.get_num_people_in_building(population) > 200 {
+                let occupants = self.get_num_people_in_building(population);
+                if occupants > 200 {
                     A_RUST_COLOR_1
-                } else if self.get_num_people_in_building(population) > 150 {
+                } else if occupants > 150 {
                     A_RUST_COLOR_2
-                } else if self.get_num_people_in_building(population) > 100 {
+                } else if occupants > 100 {
                     A_LIGHT_COLOR
-                } else if self.get_num_people_in_building(population) > 20 {
+                } else if occupants > 20 {
                     A_SAND_COLOR
                 } else {
                     A_DARKEST_COLOR
@@ -382,6 +629,92 @@ impl Drawable for Building {
 
 // ----- ROADS -----
 
+/// What a road is used for, the pavement glyph only changes how it's drawn.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, EnumString, IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RoadClass {
+    DirtPath,
+    Street,
+    Avenue,
+    Highway,
+}
+
+impl RoadClass {
+    /// Width of a horizontal road in cells, vertical roads are `TERMINAL_RATIO` times wider.
+    pub fn lanes(&self) -> u8 {
+        match self {
+            RoadClass::DirtPath | RoadClass::Street => 1,
+            RoadClass::Avenue | RoadClass::Highway => 2,
+        }
+    }
+
+    pub fn width(&self, horizontal: bool) -> u8 {
+        if horizontal {
+            self.lanes()
+        } else {
+            self.lanes() * TERMINAL_RATIO
+        }
+    }
+
+    /// In km/h
+    pub fn speed(&self) -> u8 {
+        match self {
+            RoadClass::DirtPath => 20,
+            RoadClass::Street => 40,
+            RoadClass::Avenue => 60,
+            RoadClass::Highway => 110,
+        }
+    }
+
+    /// Number of commuters a road can take each month before it's congested
+    pub fn capacity(&self) -> u32 {
+        match self {
+            RoadClass::DirtPath => 20,
+            RoadClass::Street => 80,
+            RoadClass::Avenue => 250,
+            RoadClass::Highway => 800,
+        }
+    }
+
+    /// Price of one cell of road
+    pub fn build_cost(&self) -> u32 {
+        match self {
+            RoadClass::DirtPath => 1,
+            RoadClass::Street => 5,
+            RoadClass::Avenue => 12,
+            RoadClass::Highway => 30,
+        }
+    }
+
+    pub fn pavement(&self) -> char {
+        match self {
+            RoadClass::DirtPath => '░',
+            RoadClass::Street => '▓',
+            RoadClass::Avenue | RoadClass::Highway => '█',
+        }
+    }
+
+    /// Smallest class that can take this many commuters.
+    pub fn for_demand(commuters: u32) -> Self {
+        [RoadClass::DirtPath, RoadClass::Street, RoadClass::Avenue]
+            .into_iter()
+            .find(|c| commuters <= c.capacity())
+            .unwrap_or(RoadClass::Highway)
+    }
+}
+
+impl Display for RoadClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoadClass::DirtPath => write!(f, "Dirt path"),
+            RoadClass::Street => write!(f, "Street"),
+            RoadClass::Avenue => write!(f, "Avenue"),
+            RoadClass::Highway => write!(f, "Highway"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Road {
     pub name: String,
@@ -393,6 +726,7 @@ pub struct Road {
     pub(crate) width: u8,
     pub(crate) length: u8,
     pub(crate) pavement: char,
+    pub(crate) class: RoadClass,
 }
 
 impl Road {
@@ -401,7 +735,7 @@ impl Road {
         length: u8,
         width: u8,
         is_horizontal: bool,
-        pavement: char,
+        class: RoadClass,
     ) -> Self {
         Road {
             name: "New Road".to_string(),
@@ -411,10 +745,26 @@ impl Road {
             horizontal: is_horizontal,
             width: width,
             length: length,
-            pavement: pavement,
+            pavement: class.pavement(),
+            class,
         }
     }
 
+    /// Only changes how the road is drawn.
+    pub fn with_pavement(mut self, pavement: char) -> Self {
+        self.pavement = pavement;
+        self
+    }
+
+    pub fn get_class(&self) -> RoadClass {
+        self.class
+    }
+
+    /// Price of the whole road
+    pub fn build_cost(&self) -> u32 {
+        self.length as u32 * self.width as u32 * self.class.build_cost()
+    }
+
     pub fn get_area(&self) -> Vec<(i16, i16)> {
         let mut tiles = Vec::new();
         for i in 0..self.length {
@@ -464,12 +814,15 @@ impl Road {
 }
 
 impl Clickable for Road {
-    fn infos(&self, _engine: &Engine) -> Option<Vec<String>> {
+    fn infos(&self, engine: &Engine) -> Option<Vec<String>> {
         Some(vec![
-            String::from(format!("Name: {}", self.name)),
-            String::from(format!("Position: {}, {}", self.start_x, self.start_y)),
-            String::from(format!("Length: {}", self.length)),
-            String::from(" ".to_string()), // act as a newline
+            format!("Name: {}", self.name),
+            format!("Position: {}, {}", self.start_x, self.start_y),
+            format!("Length: {}", self.length),
+            format!("Class: {} ({} km/h)", self.class, self.class.speed()),
+            format!("Capacity: {}", self.class.capacity()),
+            format!("Build cost: {}", engine.layout.road_build_cost(self)),
+            " ".to_string(), // act as a newline
         ])
     }
 }
@@ -519,14 +872,14 @@ impl Drawable for Road {
     }
 
     fn color(&self, pop: &Population) -> ansi_term::Color {
-        if self.pavement == '░' {
-            A_LIGHT_COLOR
+        if self.pavement == '#' {
+            return A_SAND_COLOR;
         }
-        else if self.pavement == '#' {
-            A_SAND_COLOR
-        }
-        else {
-            A_GREY_COLOR
+
+        match self.class {
+            RoadClass::DirtPath => A_LIGHT_COLOR,
+            RoadClass::Street | RoadClass::Avenue => A_GREY_COLOR,
+            RoadClass::Highway => A_UI_WHITE_COLOR,
         }
     }
 
@@ -541,24 +894,56 @@ impl Drawable for Road {
 // ----- LAYOUT -----
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Layout<'a> {
+pub struct Layout {
+    /// See `migrations::LAYOUT_FORMAT_VERSION`
+    #[serde(default)]
+    pub format_version: u32,
+    /// Zone of each district, buildings refer to it by its index with their `district_id`
+    pub districts: Vec<DistrictZone>,
+    /// Flat plain everywhere when None
+    pub terrain: Option<Terrain>,
     pub buildings: Vec<Building>,
     pub roads: Vec<Road>,
     #[serde(skip)]
     pub selections: Vec<Selection>,
     #[serde(skip)]
-    pub graph: Option<Graph<'a>>,
+    pub graph: Graph,
 }
 
-impl Layout<'_> {
+impl Layout {
+    pub fn new(buildings: Vec<Building>, roads: Vec<Road>) -> Self {
+        let mut layout = Layout {
+            format_version: LAYOUT_FORMAT_VERSION,
+            districts: vec![DistrictZone::Core],
+            terrain: None,
+            buildings,
+            roads,
+            selections: vec![],
+            graph: Graph::default(),
+        };
+        layout.update_graph();
+        layout
+    }
+
+    /// Rebuild the whole graph, the edit functions below already keep it up to date.
     pub fn update_graph(&mut self) {
-        unsafe { self.graph = Some(Graph::new((&raw const *self).as_ref().unwrap())) }
+        self.graph = Graph::new(self)
+    }
+
+    /// Parse a layout and upgrade it to the current format version if needed.
+    pub fn from_json(json: &str) -> Result<Self, LayoutError> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        migrate(&mut value)?;
+
+        let mut layout: Layout = serde_json::from_value(value)?;
+        layout.update_graph();
+        Ok(layout)
     }
 
     pub fn load_default_layout() -> Self {
         let layout = include_str!("../initial_data/layout.json");
 
-        let layout_obj: Layout = serde_json::from_str(layout).unwrap();
+        let layout_obj: Layout = Layout::from_json(layout).unwrap();
 
         layout_obj
     }
@@ -566,7 +951,7 @@ impl Layout<'_> {
     pub fn load_default_layout2() -> Self {
         let layout = include_str!("../initial_data/layout2.json");
 
-        let layout_obj: Layout = serde_json::from_str(layout).unwrap();
+        let layout_obj: Layout = Layout::from_json(layout).unwrap();
 
         layout_obj
     }
@@ -574,7 +959,7 @@ impl Layout<'_> {
     pub fn load_core_layout() -> Self {
         let layout = include_str!("../initial_data/starting_core.json");
 
-        let layout_obj: Layout = serde_json::from_str(layout).unwrap();
+        let layout_obj: Layout = Layout::from_json(layout).unwrap();
 
         layout_obj
     }
@@ -582,19 +967,19 @@ impl Layout<'_> {
     pub fn load_empty_layout() -> Self {
         let layout = include_str!("../initial_data/starting_empty.json");
 
-        let layout_obj: Layout = serde_json::from_str(layout).unwrap();
+        let layout_obj: Layout = Layout::from_json(layout).unwrap();
 
         layout_obj
     }
 
     pub fn add_building(&mut self, building: Building) {
+        self.graph.insert_building(&building);
         self.buildings.push(building);
-        self.update_graph();
     }
 
     pub fn add_road(&mut self, road: Road) {
+        self.graph.insert_road(&road);
         self.roads.push(road);
-        self.update_graph()
     }
 
     /// Clone the vec
@@ -613,6 +998,17 @@ impl Layout<'_> {
             .collect()
     }
 
+    /// (min x, min y, max x, max y) of every building and road, max excluded. \
+    /// None if the layout is empty.
+    pub fn bounds(&self) -> Option<(i16, i16, i16, i16)> {
+        self.roads
+            .iter()
+            .map(|r| r as &dyn Drawable)
+            .chain(self.buildings.iter().map(|b| b as &dyn Drawable))
+            .map(|d| (d.x(), d.y(), d.x() + d.width() as i16, d.y() + d.height() as i16))
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
+
     /// Clone the vec
     pub fn get_roads(&self) -> Vec<Road> {
         self.roads.iter().map(|r| r.clone()).collect()
@@ -650,39 +1046,199 @@ impl Layout<'_> {
         res
     }
 
-    pub fn add_building_from_coords(&mut self, x: i16, y: i16, width: u8, height: u8) {
+    /// Add a building drawn in the editor, return its price. \
+    /// It gets the default name of its function, nothing is built if it costs more than the `budget`.
+    pub fn add_building_from_coords(
+        &mut self,
+        x: i16,
+        y: i16,
+        width: u8,
+        height: u8,
+        function: BuildingFunction,
+        budget: i64,
+    ) -> Result<u32, String> {
+        if !self.is_buildable(x, y, width, height) {
+            return Err("A building can't be built on water".to_string());
+        }
+        let cost = self.building_build_cost(x, y, width, height);
+        if cost as i64 > budget {
+            return Err(format!("Not enough money, the building costs {}", cost));
+        }
+
         let new_bldg = Building {
-            name: "Test12".to_string(),
+            name: function.default_name().to_string(),
             id: LayoutId::random(),
             pos_x: x,
             pos_y: y,
-            district_id: 1,
+            district_id: 0,
             b_type: BuildingType::Uniform,
             width: Option::from(width),
             height: Option::from(height),
             texture: Some('█'),
             content: Some(vec![]),
+            defense: None,
+            function,
         };
+        self.graph.insert_building(&new_bldg);
         self.buildings.push(new_bldg);
-        self.update_graph();
-    }
-
-    pub fn add_road_from_coords(&mut self, x: i16, y: i16, width: u8, height: u8) {
-        let new_road = Road {
-            name: "Road12".to_string(),
-            id: Default::default(),
-            start_x: x,
-            start_y: y,
-            horizontal: if width >= height { true } else { false },
-            width: if width >= height { 1 } else { 2 },
-            length: if width >= height { width } else { height },
-            pavement: '▓',
+        Ok(cost)
+    }
+
+    /// Add a street drawn in the editor, return the price of the new part. \
+    /// Its ends snap onto the roads next to them, the roads it ends on are split at the
+    /// T-junction and it's merged with the road it extends. Roads can't go through buildings
+    /// and nothing is built if it costs more than the `budget`.
+    pub fn add_road_from_coords(&mut self, x: i16, y: i16, width: u8, height: u8, budget: i64) -> Result<u32, String> {
+        let horizontal = width >= height;
+        let class = RoadClass::Street;
+        let thickness = class.width(horizontal) as i16;
+        let (mut start, mut end, mut across) = if horizontal {
+            (x, x + width as i16, y)
+        } else {
+            (y, y + height as i16, x)
+        };
+
+        // continue a parallel road if it's a bit off
+        if let Some(r) = self.roads.iter().find(|r| {
+            let (r_start, r_end, r_across, _) = axis_ranges(*r, horizontal);
+            r.horizontal == horizontal
+                && r.width as i16 == thickness
+                && (r_across - across).abs() <= 1
+                && r_start <= end + ROAD_SNAP_DISTANCE
+                && start <= r_end + ROAD_SNAP_DISTANCE
+        }) {
+            across = axis_ranges(r, horizontal).2;
+        }
+
+        // perpendicular roads crossing the lane of the new road
+        let crossing: Vec<(i16, i16)> = self
+            .roads
+            .iter()
+            .filter(|r| r.horizontal != horizontal)
+            .map(|r| axis_ranges(r, horizontal))
+            .filter(|(_, _, r_across, r_across_end)| *r_across < across + thickness && *r_across_end > across)
+            .map(|(r_start, r_end, _, _)| (r_start, r_end))
+            .collect();
+
+        if let Some((_, r_end)) = crossing
+            .iter()
+            .filter(|(_, r_end)| (r_end - start).abs() <= ROAD_SNAP_DISTANCE)
+            .min_by_key(|(_, r_end)| (r_end - start).abs())
+        {
+            start = *r_end;
+        }
+        if let Some((r_start, _)) = crossing
+            .iter()
+            .filter(|(r_start, _)| (r_start - end).abs() <= ROAD_SNAP_DISTANCE)
+            .min_by_key(|(r_start, _)| (r_start - end).abs())
+        {
+            end = *r_start;
+        }
+
+        if end <= start {
+            return Err("The road is too short".to_string());
+        }
+        if end - start > u8::MAX as i16 {
+            return Err("The road is too long".to_string());
+        }
+
+        let (x, y, width, height) = if horizontal {
+            (start, across, (end - start) as u8, thickness as u8)
+        } else {
+            (across, start, thickness as u8, (end - start) as u8)
         };
-        self.roads.push(new_road);
-        self.update_graph();
+
+        if !self.is_buildable(x, y, width, height) {
+            return Err("A road can't be built on water".to_string());
+        }
+        if !is_area_free_except(x, y, width, height, self, AreaPartition::Building, None) {
+            return Err("A road can't go through a building".to_string());
+        }
+
+        // collinear roads that touch become one, only the cells it doesn't cover yet are paid
+        let merged = self.roads.iter().find(|r| {
+            let (r_start, r_end, r_across, _) = axis_ranges(*r, horizontal);
+            r.horizontal == horizontal
+                && r.width as i16 == thickness
+                && r.class == class
+                && r_across == across
+                && r_start <= end
+                && start <= r_end
+                && r_end.max(end) - r_start.min(start) <= u8::MAX as i16
+        });
+        let covered: Vec<(i16, i16)> = merged.map(|r| r.get_area()).unwrap_or_default();
+        let merged = merged.map(|r| r.id);
+        let new_cells: Vec<(i16, i16)> = Road::new((x, y), (end - start) as u8, thickness as u8, horizontal, class)
+            .get_area()
+            .into_iter()
+            .filter(|cell| !covered.contains(cell))
+            .collect();
+        let cost = (new_cells.len() as f32 * class.build_cost() as f32 * self.build_cost_factor(&new_cells)).round() as u32;
+        if cost as i64 > budget {
+            return Err(format!("Not enough money, the road costs {}", cost));
+        }
+
+        // T-junctions, split the roads the new one ends on
+        let junctions: Vec<LayoutId> = self
+            .roads
+            .iter()
+            .filter(|r| r.horizontal != horizontal)
+            .filter(|r| {
+                let (r_start, r_end, r_across, r_across_end) = axis_ranges(*r, horizontal);
+                (r_end == start || r_start == end) && r_across < across && r_across_end > across + thickness
+            })
+            .map(|r| r.id)
+            .collect();
+        for id in junctions {
+            self.split_road(&id, across);
+        }
+
+        if let Some(road) = merged.and_then(|id| self.roads.iter_mut().find(|r| r.id == id)) {
+            let (r_start, r_end, _, _) = axis_ranges(road, horizontal);
+            let (merged_start, merged_end) = (r_start.min(start), r_end.max(end));
+
+            if horizontal {
+                road.start_x = merged_start;
+            } else {
+                road.start_y = merged_start;
+            }
+            road.length = (merged_end - merged_start) as u8;
+            self.graph.insert_road(road);
+            return Ok(cost);
+        }
+
+        self.add_road(Road::new((x, y), (end - start) as u8, thickness as u8, horizontal, class));
+        Ok(cost)
+    }
+
+    /// Cut the road in two at `at` (a coordinate along the road), return the id of the second part.
+    pub fn split_road(&mut self, road_id: &LayoutId, at: i16) -> Option<LayoutId> {
+        let road = self.roads.iter_mut().find(|r| r.id == *road_id)?;
+        let start = if road.horizontal { road.start_x } else { road.start_y };
+
+        if at <= start || at >= start + road.length as i16 {
+            return None;
+        }
+
+        let mut second = road.clone();
+        second.id = LayoutId::random();
+        second.length = (start + road.length as i16 - at) as u8;
+        if second.horizontal {
+            second.start_x = at;
+        } else {
+            second.start_y = at;
+        }
+        road.length = (at - start) as u8;
+
+        self.graph.insert_road(road);
+        let id = second.id;
+        self.add_road(second);
+        Some(id)
     }
 
-    pub fn replace_empty_building(&mut self, building_id: LayoutId) {
+    /// Build on the empty lot and return the price, the new building gets the default name of
+    /// its function. Nothing is built if it costs more than the `budget`.
+    pub fn replace_empty_building(&mut self, building_id: LayoutId, function: BuildingFunction, budget: i64) -> Result<u32, String> {
         let mut i = 0;
         let mut building: Option<&Building> = None;
 
@@ -696,70 +1252,413 @@ impl Layout<'_> {
             i += 1
         }
 
-        if !building.is_none() {
-            let bldg = building.unwrap();
-            //debug!("{:?}", bldg);
-
-            let new_bldg = Building {
-                name: "Test12".to_string(),
-                id: LayoutId::random(),
-                pos_x: bldg.pos_x,
-                pos_y: bldg.pos_y,
-                district_id: bldg.district_id,
-                b_type: BuildingType::Uniform,
-                width: Option::from(bldg.width()),
-                height: Option::from(bldg.height()),
-                texture: Some('▓'),
-                content: Some(vec![]),
-            };
-            self.buildings.push(new_bldg);
-            self.buildings.remove(i);
-        }
-
-        self.update_graph()
-    }
-
-    pub fn calculate_path(&mut self, start: &LayoutId, goal: &LayoutId) -> Vec<Option<Rect>> {
-        let mut wonder_graph = Graph::new(&self);
-        wonder_graph.start_dfs(&self);
-        let path = wonder_graph.find_path_bfs(start, goal);
-
-        let mut last_drawable: Option<Box<dyn Drawable>> = None;
-        let mut intersections = vec![];
-        if let Some(chemin) = path {
-            //println!("Itinéraire trouvé:");
-            for id in chemin {
-                for bldg in self.buildings.iter().filter(|x| x.id == id) {
-                    //println!("{:?}", bldg.name);
-                    /*if last_drawable.is_some(){
-                        println!("{:?}", intersection(&*last_drawable.unwrap(), &*Box::new(bldg.clone())))
-                    }*/
-                    if last_drawable.is_some() {
-                        intersections.push(intersection(
-                            &*last_drawable.unwrap(),
-                            &*Box::new(bldg.clone()),
-                        ));
+        let Some(bldg) = building else {
+            return Err("There is no empty lot here".to_string());
+        };
+
+        let cost = self.building_build_cost(bldg.pos_x, bldg.pos_y, bldg.width(), bldg.height());
+        if cost as i64 > budget {
+            return Err(format!("Not enough money, the building costs {}", cost));
+        }
+
+        let new_bldg = Building {
+            name: function.default_name().to_string(),
+            id: LayoutId::random(),
+            pos_x: bldg.pos_x,
+            pos_y: bldg.pos_y,
+            district_id: bldg.district_id,
+            b_type: BuildingType::Uniform,
+            width: Option::from(bldg.width()),
+            height: Option::from(bldg.height()),
+            texture: Some('▓'),
+            content: None,
+            defense: None,
+            function,
+        };
+        self.graph.remove(&building_id);
+        self.graph.insert_building(&new_bldg);
+        self.buildings.push(new_bldg);
+        self.buildings.remove(i);
+        Ok(cost)
+    }
+
+    // ----- EDIT TOOLS -----
+
+    /// Remove the building from the layout and return it. \
+    /// The peoples inside should be evicted by the caller (see `Population::evict_building`).
+    pub fn remove_building(&mut self, building_id: &LayoutId) -> Option<Building> {
+        let index = self.buildings.iter().position(|b| b.id == *building_id)?;
+        let removed = self.buildings.remove(index);
+        self.graph.remove(building_id);
+        Some(removed)
+    }
+
+    pub fn remove_road(&mut self, road_id: &LayoutId) -> Option<Road> {
+        let index = self.roads.iter().position(|r| r.id == *road_id)?;
+        let removed = self.roads.remove(index);
+        self.graph.remove(road_id);
+        Some(removed)
+    }
+
+    /// Turn the building into an empty lot of the same size, the people inside should be evicted
+    /// by the caller (see `Population::evict_building`).
+    pub fn clear_building(&mut self, building_id: &LayoutId) -> bool {
+        let Some(bldg) = self.buildings.iter_mut().find(|b| b.id == *building_id) else {
+            return false;
+        };
+
+        let (width, height) = (bldg.width(), bldg.height());
+        *bldg = Building {
+            name: "Empty Lot".to_string(),
+            b_type: BuildingType::EmptySpace,
+            width: Some(width),
+            height: Some(height),
+            texture: None,
+            content: None,
+            defense: None,
+            ..bldg.clone()
+        };
+        true
+    }
+
+    /// Change what the building is used for, the people living or working there stay.
+    pub fn set_function(&mut self, building_id: &LayoutId, function: BuildingFunction) -> bool {
+        match self.buildings.iter_mut().find(|b| b.id == *building_id) {
+            Some(bldg) => {
+                bldg.function = function;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turn the building into a guard post or a wall, the shape and the graph don't change.
+    pub fn set_defense(&mut self, building_id: &LayoutId, defense: Option<Defense>) -> bool {
+        match self.buildings.iter_mut().find(|b| b.id == *building_id) {
+            Some(bldg) => {
+                bldg.defense = defense;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the building so its top left corner is at (x, y), return the price of the cells it didn't cover yet. \
+    /// Nothing moves if the new position overlaps something, is on water or costs more than the `budget`.
+    pub fn move_building(&mut self, building_id: &LayoutId, x: i16, y: i16, budget: i64) -> Result<u32, String> {
+        let Some(bldg) = self.buildings.iter().find(|b| b.id == *building_id) else {
+            return Err("This building doesn't exist".to_string());
+        };
+
+        let mut moved = bldg.clone();
+        moved.pos_x = x;
+        moved.pos_y = y;
+        let cost = self.edit_cost(&bldg.get_area(), &moved.get_area(), BUILDING_CELL_COST, budget)?;
+
+        if !is_area_free_except(x, y, bldg.width(), bldg.height(), self, AreaPartition::All, Some(building_id)) {
+            return Err("Can't move here, the area is not free".to_string());
+        }
+
+        let bldg = self.buildings.iter_mut().find(|b| b.id == *building_id).unwrap();
+        bldg.pos_x = x;
+        bldg.pos_y = y;
+        self.graph.insert_building(bldg);
+        Ok(cost)
+    }
+
+    /// Roads are only checked against buildings since they can cross other roads.
+    pub fn move_road(&mut self, road_id: &LayoutId, x: i16, y: i16, budget: i64) -> Result<u32, String> {
+        let Some(road) = self.roads.iter().find(|r| r.id == *road_id) else {
+            return Err("This road doesn't exist".to_string());
+        };
+
+        let mut moved = road.clone();
+        moved.start_x = x;
+        moved.start_y = y;
+        let cost = self.edit_cost(&road.get_area(), &moved.get_area(), road.class.build_cost(), budget)?;
+
+        if !is_area_free_except(x, y, road.width(), road.height(), self, AreaPartition::Building, Some(road_id)) {
+            return Err("Can't move here, the area is not free".to_string());
+        }
+
+        let road = self.roads.iter_mut().find(|r| r.id == *road_id).unwrap();
+        road.start_x = x;
+        road.start_y = y;
+        self.graph.insert_road(road);
+        Ok(cost)
+    }
+
+    /// Custom buildings can't be resized since their shape comes from their content.
+    pub fn resize_building(&mut self, building_id: &LayoutId, width: u8, height: u8, budget: i64) -> Result<u32, String> {
+        let Some(bldg) = self.buildings.iter().find(|b| b.id == *building_id) else {
+            return Err("This building doesn't exist".to_string());
+        };
+
+        if bldg.b_type == BuildingType::Custom {
+            return Err("This building can't be resized".to_string());
+        }
+        if width == 0 || height == 0 {
+            return Err("The building is too small".to_string());
+        }
+
+        let mut resized = bldg.clone();
+        resized.width = Some(width);
+        resized.height = Some(height);
+        let cost = self.edit_cost(&bldg.get_area(), &resized.get_area(), BUILDING_CELL_COST, budget)?;
+
+        if !is_area_free_except(bldg.pos_x, bldg.pos_y, width, height, self, AreaPartition::All, Some(building_id)) {
+            return Err("Can't resize, the area is not free".to_string());
+        }
+
+        let bldg = self.buildings.iter_mut().find(|b| b.id == *building_id).unwrap();
+        bldg.width = Some(width);
+        bldg.height = Some(height);
+        self.graph.insert_building(bldg);
+        Ok(cost)
+    }
+
+    /// Change the length of the road, its start and direction stay the same.
+    pub fn resize_road(&mut self, road_id: &LayoutId, length: u8, budget: i64) -> Result<u32, String> {
+        let Some(road) = self.roads.iter().find(|r| r.id == *road_id) else {
+            return Err("This road doesn't exist".to_string());
+        };
+
+        if length == 0 {
+            return Err("The road is too short".to_string());
+        }
+
+        let mut resized = road.clone();
+        resized.length = length;
+        let cost = self.edit_cost(&road.get_area(), &resized.get_area(), road.class.build_cost(), budget)?;
+
+        if !is_area_free_except(road.start_x, road.start_y, resized.width(), resized.height(), self, AreaPartition::Building, Some(road_id)) {
+            return Err("Can't resize, the area is not free".to_string());
+        }
+
+        let road = self.roads.iter_mut().find(|r| r.id == *road_id).unwrap();
+        road.length = length;
+        self.graph.insert_road(road);
+        Ok(cost)
+    }
+
+    /// Shortest route by road between two buildings, see `routing::find_route`.
+    pub fn calculate_path(&self, start: &LayoutId, goal: &LayoutId) -> Option<Route> {
+        find_route(self, start, goal)
+    }
+}
+
+// ----- TERRAIN -----
+
+impl Layout {
+    pub fn terrain_at(&self, x: i16, y: i16) -> TerrainKind {
+        match &self.terrain {
+            Some(terrain) => terrain.kind_at(x, y),
+            None => TerrainKind::Plain,
+        }
+    }
+
+    /// False if part of the area is on water.
+    pub fn is_buildable(&self, x: i16, y: i16, width: u8, height: u8) -> bool {
+        self.terrain.is_none()
+            || (y..y + height as i16).all(|y| (x..x + width as i16).all(|x| self.terrain_at(x, y).is_buildable()))
+    }
+
+    /// Average `TerrainKind::build_cost_factor` of the area
+    pub fn build_cost_factor(&self, area: &[(i16, i16)]) -> f32 {
+        if area.is_empty() {
+            return 1.0;
+        }
+        area.iter().map(|(x, y)| self.terrain_at(*x, *y).build_cost_factor()).sum::<f32>() / area.len() as f32
+    }
+
+    /// Price of the road once the terrain under it is taken into account
+    pub fn road_build_cost(&self, road: &Road) -> u32 {
+        (road.build_cost() as f32 * self.build_cost_factor(&road.get_area())).round() as u32
+    }
+
+    /// Price of the cells of `area` that `old` doesn't cover, what moving or resizing something costs. \
+    /// Err if part of `area` is on water or if it costs more than the `budget`.
+    pub fn edit_cost(&self, old: &[(i16, i16)], area: &[(i16, i16)], cell_cost: u32, budget: i64) -> Result<u32, String> {
+        if self.terrain.is_some() && area.iter().any(|(x, y)| !self.terrain_at(*x, *y).is_buildable()) {
+            return Err("Nothing can be built on water".to_string());
+        }
+
+        let new_cells: Vec<(i16, i16)> = area.iter().filter(|cell| !old.contains(cell)).copied().collect();
+        let cost = (new_cells.len() as f32 * cell_cost as f32 * self.build_cost_factor(&new_cells)).round() as u32;
+        if cost as i64 > budget {
+            return Err(format!("Not enough money, it costs {}", cost));
+        }
+        Ok(cost)
+    }
+
+    /// Price of a new rectangle building, `BUILDING_CELL_COST` per cell before the terrain
+    pub fn building_build_cost(&self, x: i16, y: i16, width: u8, height: u8) -> u32 {
+        let area: Vec<(i16, i16)> =
+            (y..y + height as i16).flat_map(|cy| (x..x + width as i16).map(move |cx| (cx, cy))).collect();
+        (area.len() as f32 * BUILDING_CELL_COST as f32 * self.build_cost_factor(&area)).round() as u32
+    }
+}
+
+// ----- VALIDATION -----
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The layout can't be loaded
+    Error,
+    /// The layout can be loaded but something looks wrong
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+impl LayoutIssue {
+    fn error(message: String) -> Self {
+        LayoutIssue { severity: IssueSeverity::Error, message }
+    }
+
+    fn warning(message: String) -> Self {
+        LayoutIssue { severity: IssueSeverity::Warning, message }
+    }
+}
+
+impl Display for LayoutIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            IssueSeverity::Error => write!(f, "error: {}", self.message),
+            IssueSeverity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+impl Display for Building {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "building \"{}\" ({:?})", self.name, self.id)
+    }
+}
+
+impl Display for Road {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "road \"{}\" ({:?})", self.name, self.id)
+    }
+}
+
+/// (x1, y1, x2, y2), x2 and y2 excluded
+fn bounds(d: &dyn Drawable) -> (i16, i16, i16, i16) {
+    (d.x(), d.y(), d.x() + d.width() as i16, d.y() + d.height() as i16)
+}
+
+/// (start, end, across start, across end) of a drawable along the x axis if `horizontal`, the y axis otherwise
+fn axis_ranges(d: &dyn Drawable, horizontal: bool) -> (i16, i16, i16, i16) {
+    let (x1, y1, x2, y2) = bounds(d);
+    if horizontal {
+        (x1, x2, y1, y2)
+    } else {
+        (y1, y2, x1, x2)
+    }
+}
+
+fn overlap(a: (i16, i16, i16, i16), b: (i16, i16, i16, i16)) -> bool {
+    a.0 < b.2 && a.2 > b.0 && a.1 < b.3 && a.3 > b.1
+}
+
+impl Layout {
+    /// Check the whole layout and return every problem found, it will not stop at the first one. \
+    /// Overlaps and road access are only checked when every shape is valid since they need the sizes.
+    pub fn validate(&self) -> Vec<LayoutIssue> {
+        let mut issues = vec![];
+
+        if !self.districts.contains(&DistrictZone::Core) {
+            issues.push(LayoutIssue::error("The layout has no core district".to_string()));
+        }
+
+        let mut ids: HashMap<LayoutId, String> = HashMap::new();
+        for (id, name) in self
+            .buildings
+            .iter()
+            .map(|b| (b.id, b.to_string()))
+            .chain(self.roads.iter().map(|r| (r.id, r.to_string())))
+        {
+            if let Some(first) = ids.get(&id) {
+                issues.push(LayoutIssue::error(format!("{} uses the same id as {}", name, first)));
+            } else {
+                ids.insert(id, name);
+            }
+        }
+
+        for bldg in &self.buildings {
+            match (&bldg.b_type, &bldg.content) {
+                (BuildingType::Custom, None) => {
+                    issues.push(LayoutIssue::error(format!("{} is custom but has no content", bldg)))
+                }
+                (BuildingType::Custom, Some(content)) if content.is_empty() => {
+                    issues.push(LayoutIssue::error(format!("{} is custom but its content is empty", bldg)))
+                }
+                (BuildingType::Custom, Some(content)) => {
+                    let expected = content[0].chars().count();
+                    for (i, line) in content.iter().enumerate().skip(1) {
+                        if line.chars().count() != expected {
+                            issues.push(LayoutIssue::error(format!(
+                                "{} has ragged content, line {} is {} chars wide instead of {}",
+                                bldg,
+                                i + 1,
+                                line.chars().count(),
+                                expected
+                            )));
+                        }
                     }
-                    last_drawable = Some(Box::new(bldg.clone()))
                 }
-                for rdg in self.roads.iter().filter(|x| x.id == id) {
-                    //println!("{:?}", rdg.name);
-
-                    /*if last_drawable.is_some(){
-                        println!("{:?}", intersection(&*last_drawable.unwrap(), &*Box::new(rdg.clone())))
-                    }*/
-                    if last_drawable.is_some() {
-                        intersections.push(intersection(
-                            &*last_drawable.unwrap(),
-                            &*Box::new(rdg.clone()),
-                        ));
+                _ => {
+                    if bldg.width.unwrap_or(0) == 0 || bldg.height.unwrap_or(0) == 0 {
+                        issues.push(LayoutIssue::error(format!(
+                            "{} is {} but is missing its width or height",
+                            bldg, bldg.b_type
+                        )));
                     }
-                    last_drawable = Some(Box::new(rdg.clone()))
                 }
             }
-        } else {
-            return intersections;
+
+            if bldg.district_id >= self.districts.len() {
+                issues.push(LayoutIssue::error(format!(
+                    "{} is in the unknown district {}",
+                    bldg, bldg.district_id
+                )));
+            }
+        }
+
+        for road in &self.roads {
+            if road.length == 0 || road.width == 0 {
+                issues.push(LayoutIssue::error(format!("{} has no length or width", road)));
+            }
+        }
+
+        if issues.iter().any(|i| i.severity == IssueSeverity::Error) {
+            return issues;
+        }
+
+        for (i, bldg) in self.buildings.iter().enumerate() {
+            for other in &self.buildings[i + 1..] {
+                if overlap(bounds(bldg), bounds(other)) {
+                    issues.push(LayoutIssue::error(format!("{} overlaps {}", bldg, other)));
+                }
+            }
+
+            for road in &self.roads {
+                if overlap(bounds(bldg), bounds(road)) {
+                    issues.push(LayoutIssue::error(format!("{} overlaps {}", bldg, road)));
+                }
+            }
         }
-        intersections
+
+        for bldg in &self.buildings {
+            if self.graph.connected_to(&bldg.id).is_empty() {
+                issues.push(LayoutIssue::warning(format!("{} can't be reached by any road", bldg)));
+            }
+        }
+
+        issues
     }
 }
@@ -0,0 +1,159 @@
+use serde_json::{Map, Value};
+use std::fmt::{Display, Formatter};
+
+/// Version written in every saved layout, bump it and add a migration to `MIGRATIONS`
+/// every time a field of `Layout`, `Building` or `Road` changes.
+pub const LAYOUT_FORMAT_VERSION: u32 = 6;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a layout from version `n` to version `n + 1`.
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6];
+
+#[derive(Debug)]
+pub enum LayoutError {
+    Json(serde_json::Error),
+    /// The file was written by a newer version of the game
+    UnsupportedVersion(u32),
+    Migration { from: u32, message: String },
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::Json(e) => write!(f, "{}", e),
+            LayoutError::UnsupportedVersion(v) => write!(
+                f,
+                "format version {} is newer than the supported version {}",
+                v, LAYOUT_FORMAT_VERSION
+            ),
+            LayoutError::Migration { from, message } => {
+                write!(f, "could not upgrade from version {}: {}", from, message)
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for LayoutError {
+    fn from(value: serde_json::Error) -> Self {
+        LayoutError::Json(value)
+    }
+}
+
+/// Files written before versioning have no `format_version` and are version 0.
+pub fn get_format_version(layout: &Value) -> u32 {
+    layout
+        .get("format_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Run every migration needed to bring the layout to `LAYOUT_FORMAT_VERSION`. \
+/// Return the version the layout had before.
+pub fn migrate(layout: &mut Value) -> Result<u32, LayoutError> {
+    let original = get_format_version(layout);
+
+    if original > LAYOUT_FORMAT_VERSION {
+        return Err(LayoutError::UnsupportedVersion(original));
+    }
+
+    let Some(obj) = layout.as_object_mut() else {
+        return Err(LayoutError::Migration {
+            from: original,
+            message: "the layout is not a JSON object".to_string(),
+        });
+    };
+
+    for version in original..LAYOUT_FORMAT_VERSION {
+        MIGRATIONS[version as usize](obj).map_err(|message| LayoutError::Migration {
+            from: version,
+            message,
+        })?;
+        obj.insert("format_version".to_string(), Value::from(version + 1));
+    }
+
+    Ok(original)
+}
+
+fn objects_in<'a>(
+    layout: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<Vec<&'a mut Map<String, Value>>, String> {
+    match layout.get_mut(key) {
+        Some(Value::Array(items)) => items
+            .iter_mut()
+            .map(|i| i.as_object_mut().ok_or(format!("an entry of `{}` is not an object", key)))
+            .collect(),
+        None => Ok(vec![]),
+        _ => Err(format!("`{}` is not an array", key)),
+    }
+}
+
+// ----- MIGRATIONS -----
+
+/// Drop the `selections` saved by the editor and the unused `district_id` of roads, these
+/// layouts only had the Core district (see `v2_to_v3`) so any other id is an error.
+fn v0_to_v1(layout: &mut Map<String, Value>) -> Result<(), String> {
+    layout.remove("selections");
+
+    for road in objects_in(layout, "roads")? {
+        if let Some(district) = road.remove("district_id").filter(|d| d.as_u64() != Some(0)) {
+            let name = road.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+            return Err(format!("road \"{}\" is in the unknown district {}", name, district));
+        }
+    }
+
+    Ok(())
+}
+
+/// Give every road without one a `class`, guessed from its pavement and width.
+fn v1_to_v2(layout: &mut Map<String, Value>) -> Result<(), String> {
+    for road in objects_in(layout, "roads")? {
+        let horizontal = road.get("horizontal").and_then(|h| h.as_bool()).unwrap_or(true);
+        let width = road.get("width").and_then(|w| w.as_u64()).unwrap_or(1);
+        let lanes = if horizontal { width } else { width / 2 };
+
+        let class = match road.get("pavement").and_then(|p| p.as_str()) {
+            Some("░") => "dirt_path",
+            _ if lanes >= 2 => "avenue",
+            _ => "street",
+        };
+        road.entry("class").or_insert(Value::from(class));
+    }
+
+    Ok(())
+}
+
+/// Older layouts only had the Core district.
+fn v2_to_v3(layout: &mut Map<String, Value>) -> Result<(), String> {
+    if !layout.contains_key("districts") {
+        layout.insert("districts".to_string(), Value::from(vec!["core"]));
+    }
+
+    Ok(())
+}
+
+/// Older layouts were built on a flat plain.
+fn v3_to_v4(layout: &mut Map<String, Value>) -> Result<(), String> {
+    layout.entry("terrain").or_insert(Value::Null);
+
+    Ok(())
+}
+
+/// No building was defended against monsters.
+fn v4_to_v5(layout: &mut Map<String, Value>) -> Result<(), String> {
+    for building in objects_in(layout, "buildings")? {
+        building.entry("defense").or_insert(Value::Null);
+    }
+
+    Ok(())
+}
+
+/// Every building was used as housing.
+fn v5_to_v6(layout: &mut Map<String, Value>) -> Result<(), String> {
+    for building in objects_in(layout, "buildings")? {
+        building.entry("function").or_insert(Value::from("housing"));
+    }
+
+    Ok(())
+}
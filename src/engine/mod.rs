@@ -2,4 +2,8 @@ pub mod drawable;
 pub mod viewport;
 pub mod core;
 pub mod keybinds;
-pub mod layout;
\ No newline at end of file
+pub mod layout;
+pub mod migrations;
+pub mod ascii_map;
+pub mod snapshot;
+pub mod terrain;
\ No newline at end of file
@@ -0,0 +1,210 @@
+//! Standalone SVG and HTML pictures of the city, to share it without a terminal.
+
+use crate::engine::drawable::Drawable;
+use crate::engine::layout::{BuildingType, Layout};
+use crate::engine::terrain::TerrainKind;
+use crate::engine::viewport::background;
+use crate::population::Population;
+use crate::ui::colors::{A_GREY_COLOR, UI_BLACK_COLOR, UI_BLACK_LIGHT_COLOR};
+use ansi_term::Colour;
+use termion::color::Rgb;
+
+/// Size of one terminal cell in the SVG, a cell is twice as high as it is wide.
+const CELL_WIDTH: i32 = 8;
+const CELL_HEIGHT: i32 = 16;
+/// Empty cells around the city
+const MARGIN: i16 = 2;
+
+fn hex(color: Colour) -> String {
+    match color {
+        Colour::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => hex(A_GREY_COLOR),
+    }
+}
+
+fn hex_rgb(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn padded_bounds(layout: &Layout) -> (i16, i16, i16, i16) {
+    let (min_x, min_y, max_x, max_y) = layout.bounds().unwrap_or((0, 0, 0, 0));
+    (min_x - MARGIN, min_y - MARGIN, max_x + MARGIN, max_y + MARGIN)
+}
+
+/// Buildings are coloured by their population like in the terminal, hovering them shows
+/// their name and number of inhabitants. Plain land is left blank.
+pub fn export_svg(layout: &Layout, population: &Population) -> String {
+    let (min_x, min_y, max_x, max_y) = padded_bounds(layout);
+    let rect = |x: i16, y: i16, w: i16, h: i16| {
+        format!(
+            "x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+            (x - min_x) as i32 * CELL_WIDTH,
+            (y - min_y) as i32 * CELL_HEIGHT,
+            w as i32 * CELL_WIDTH,
+            h as i32 * CELL_HEIGHT
+        )
+    };
+
+    let width = (max_x - min_x) as i32 * CELL_WIDTH;
+    let height = (max_y - min_y) as i32 * CELL_HEIGHT;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    svg += &format!("<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex_rgb(UI_BLACK_COLOR));
+
+    if let Some(terrain) = &layout.terrain {
+        svg += "<g id=\"terrain\">\n";
+        for y in min_y..max_y {
+            // one rect per run of the same terrain
+            let mut x = min_x;
+            while x < max_x {
+                let kind = terrain.kind_at(x, y);
+                let len = (x..max_x).take_while(|x2| terrain.kind_at(*x2, y) == kind).count() as i16;
+                if kind != TerrainKind::Plain {
+                    svg += &format!("<rect {} fill=\"{}\"/>\n", rect(x, y, len, 1), hex(kind.color()));
+                }
+                x += len;
+            }
+        }
+        svg += "</g>\n";
+    }
+
+    svg += "<g id=\"roads\">\n";
+    for road in &layout.roads {
+        svg += &format!(
+            "<rect {} fill=\"{}\"><title>{}</title></rect>\n",
+            rect(road.x(), road.y(), road.width() as i16, road.height() as i16),
+            hex(road.color(population)),
+            escape(&road.name)
+        );
+    }
+    svg += "</g>\n<g id=\"buildings\">\n";
+
+    for bldg in &layout.buildings {
+        let color = hex(bldg.color(population));
+        svg += &format!(
+            "<g><title>{} ({} people)</title>",
+            escape(bldg.get_name()),
+            bldg.get_num_people_in_building(population)
+        );
+
+        match (bldg.get_building_type(), bldg.get_content()) {
+            (BuildingType::EmptySpace, _) => {
+                svg += &format!(
+                    "<rect {} fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                    rect(bldg.x(), bldg.y(), bldg.width() as i16, bldg.height() as i16),
+                    color
+                )
+            }
+            (BuildingType::Custom, Some(content)) => {
+                // one rect per run of non blank characters
+                for (dy, line) in content.iter().enumerate() {
+                    let chars: Vec<char> = line.chars().collect();
+                    let mut dx = 0;
+                    while dx < chars.len() {
+                        let len = chars[dx..].iter().take_while(|c| **c != ' ').count();
+                        if len > 0 {
+                            svg += &format!(
+                                "<rect {} fill=\"{}\"/>",
+                                rect(bldg.x() + dx as i16, bldg.y() + dy as i16, len as i16, 1),
+                                color
+                            );
+                        }
+                        dx += len + 1;
+                    }
+                }
+            }
+            _ => {
+                svg += &format!(
+                    "<rect {} fill=\"{}\"/>",
+                    rect(bldg.x(), bldg.y(), bldg.width() as i16, bldg.height() as i16),
+                    color
+                )
+            }
+        }
+
+        svg += "</g>\n";
+    }
+
+    svg += "</g>\n</svg>\n";
+    svg
+}
+
+/// Same characters and colours as `Engine::refresh` would draw, in a `<pre>` block.
+pub fn export_html(layout: &Layout, population: &Population) -> String {
+    let (min_x, min_y, max_x, max_y) = padded_bounds(layout);
+    let (width, height) = ((max_x - min_x) as usize, (max_y - min_y) as usize);
+
+    let mut grid: Vec<Vec<(char, String)>> = match &layout.terrain {
+        Some(terrain) => (min_y..max_y)
+            .map(|y| {
+                (min_x..max_x)
+                    .map(|x| {
+                        let (c, color) = terrain.glyph_at(x, y);
+                        (c, hex(color))
+                    })
+                    .collect()
+            })
+            .collect(),
+        None => {
+            let bg_color = hex_rgb(UI_BLACK_LIGHT_COLOR);
+            background(0, width as u16, height as u16)
+                .lines()
+                .map(|l| l.chars().map(|c| (c, bg_color.clone())).collect())
+                .collect()
+        }
+    };
+
+    let drawables = layout
+        .buildings
+        .iter()
+        .map(|b| b as &dyn Drawable)
+        .chain(layout.roads.iter().map(|r| r as &dyn Drawable));
+
+    for d in drawables {
+        let color = hex(d.color(population));
+        for (dy, line) in d.shape().lines().take(d.height() as usize).enumerate() {
+            for (dx, c) in line.chars().take(d.width() as usize).enumerate() {
+                let x = (d.x() - min_x) as usize + dx;
+                let y = (d.y() - min_y) as usize + dy;
+                grid[y][x] = (c, color.clone());
+            }
+        }
+    }
+
+    let mut pre = String::new();
+    for line in grid {
+        let mut current: Option<String> = None;
+        for (c, color) in line {
+            if current.as_ref() != Some(&color) {
+                if current.is_some() {
+                    pre += "</span>";
+                }
+                pre += &format!("<span style=\"color:{}\">", color);
+                current = Some(color);
+            }
+            pre += &escape(&c.to_string());
+        }
+        if current.is_some() {
+            pre += "</span>";
+        }
+        pre.push('\n');
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Rustupolis</title>\n\
+         <style>body {{ background: {}; }} pre {{ font-family: monospace; line-height: 1; }}</style>\n\
+         </head>\n<body>\n<pre>\n{}</pre>\n</body>\n</html>\n",
+        hex_rgb(UI_BLACK_COLOR),
+        pre
+    )
+}
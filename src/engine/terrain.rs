@@ -0,0 +1,159 @@
+use crate::engine::layout::TERMINAL_RATIO;
+use crate::ui::colors::{
+    A_GREY_BLUE_COLOR, A_GREY_COLOR, A_RUST_COLOR_2, A_UI_BLACK_LIGHT_COLOR, A_UI_WHITE_DARKER_COLOR,
+};
+use serde::{Deserialize, Serialize};
+
+/// Size in cells of the hills and lakes
+const ELEVATION_SCALE: f64 = 14.0;
+/// Size in cells of the irradiated wastelands
+const RADIATION_SCALE: f64 = 20.0;
+/// Changes the seed of the radiation noise so it doesn't follow the elevation
+const RADIATION_SALT: u64 = 0x5eed_da7a_b0b0_cafe;
+
+const WATER_LEVEL: f64 = 0.3;
+/// Land just above the water is fertile
+const FERTILE_LEVEL: f64 = 0.38;
+const ROUGH_LEVEL: f64 = 0.68;
+const RADIATION_LEVEL: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerrainKind {
+    Plain,
+    Water,
+    Rough,
+    Fertile,
+    Irradiated,
+}
+
+impl TerrainKind {
+    /// Nothing can be built on water
+    pub fn is_buildable(&self) -> bool {
+        *self != TerrainKind::Water
+    }
+
+    /// Multiplier of the price of anything built on this terrain
+    pub fn build_cost_factor(&self) -> f32 {
+        match self {
+            TerrainKind::Plain | TerrainKind::Fertile => 1.0,
+            TerrainKind::Rough => 2.0,
+            // the ground needs to be cleaned first
+            TerrainKind::Irradiated => 1.5,
+            TerrainKind::Water => f32::INFINITY,
+        }
+    }
+
+    /// People fed each month by one cell of building on this terrain
+    pub fn food_production(&self) -> f32 {
+        match self {
+            TerrainKind::Plain => 2.0,
+            TerrainKind::Fertile => 5.0,
+            TerrainKind::Rough => 1.0,
+            TerrainKind::Irradiated => 0.5,
+            TerrainKind::Water => 0.0,
+        }
+    }
+
+    pub fn glyph(&self) -> char {
+        match self {
+            TerrainKind::Plain => '▖',
+            TerrainKind::Water => '≈',
+            TerrainKind::Rough => '^',
+            TerrainKind::Fertile => '"',
+            TerrainKind::Irradiated => '░',
+        }
+    }
+
+    pub fn color(&self) -> ansi_term::Color {
+        match self {
+            TerrainKind::Plain => A_UI_BLACK_LIGHT_COLOR,
+            TerrainKind::Water => A_GREY_BLUE_COLOR,
+            TerrainKind::Rough => A_GREY_COLOR,
+            TerrainKind::Fertile => A_UI_WHITE_DARKER_COLOR,
+            TerrainKind::Irradiated => A_RUST_COLOR_2,
+        }
+    }
+}
+
+/// Ground under the city, generated from value noise so only the seed needs to be saved. \
+/// It covers the whole map, even where nothing is built yet.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Terrain {
+    pub seed: u64,
+}
+
+impl Terrain {
+    pub fn new(seed: u64) -> Self {
+        Terrain { seed }
+    }
+
+    pub fn kind_at(&self, x: i16, y: i16) -> TerrainKind {
+        // cells are twice as high as they are wide
+        let (fx, fy) = (x as f64 / TERMINAL_RATIO as f64, y as f64);
+
+        let elevation = fractal_noise(self.seed, fx / ELEVATION_SCALE, fy / ELEVATION_SCALE);
+        if elevation < WATER_LEVEL {
+            return TerrainKind::Water;
+        }
+
+        let radiation =
+            fractal_noise(self.seed ^ RADIATION_SALT, fx / RADIATION_SCALE, fy / RADIATION_SCALE);
+        if radiation > RADIATION_LEVEL {
+            TerrainKind::Irradiated
+        } else if elevation < FERTILE_LEVEL {
+            TerrainKind::Fertile
+        } else if elevation > ROUGH_LEVEL {
+            TerrainKind::Rough
+        } else {
+            TerrainKind::Plain
+        }
+    }
+
+    /// Character and colour drawn in the background, plain land is only dotted here and there.
+    pub fn glyph_at(&self, x: i16, y: i16) -> (char, ansi_term::Color) {
+        match self.kind_at(x, y) {
+            TerrainKind::Plain if !hash(self.seed, x as i64, y as i64).is_multiple_of(9) => (' ', TerrainKind::Plain.color()),
+            kind => (kind.glyph(), kind.color()),
+        }
+    }
+
+    /// Share of the cells of the area on each terrain kind
+    pub fn share_of(&self, area: &[(i16, i16)], kind: TerrainKind) -> f64 {
+        if area.is_empty() {
+            return 0.0;
+        }
+        area.iter().filter(|(x, y)| self.kind_at(*x, *y) == kind).count() as f64 / area.len() as f64
+    }
+}
+
+// ----- NOISE -----
+
+/// splitmix64 of the seed and the lattice point
+fn hash(seed: u64, x: i64, y: i64) -> u64 {
+    let mut z = seed
+        .wrapping_add((x as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .wrapping_add((y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Between 0 and 1
+fn lattice(seed: u64, x: i64, y: i64) -> f64 {
+    (hash(seed, x, y) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn value_noise(seed: u64, x: f64, y: f64) -> f64 {
+    let (x0, y0) = (x.floor() as i64, y.floor() as i64);
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0 as f64), smooth(y - y0 as f64));
+
+    let top = lattice(seed, x0, y0) * (1.0 - tx) + lattice(seed, x0 + 1, y0) * tx;
+    let bottom = lattice(seed, x0, y0 + 1) * (1.0 - tx) + lattice(seed, x0 + 1, y0 + 1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Two octaves so the shores are not too smooth
+fn fractal_noise(seed: u64, x: f64, y: f64) -> f64 {
+    0.7 * value_noise(seed, x, y) + 0.3 * value_noise(seed.wrapping_add(1), x * 2.0, y * 2.0)
+}
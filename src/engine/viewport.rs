@@ -72,6 +72,14 @@ impl Viewport {
         (((x - self.output_x) as i16) + self.virtual_x, ((y- self.output_y) as i16) + self.virtual_y)
     }
 
+    /// Position in the terminal of a single cell, None if it is out of the viewport
+    pub fn get_output_point(&self, x: i16, y: i16) -> Option<(u16, u16)> {
+        if x < self.virtual_x || x >= self.right() || y < self.virtual_y || y >= self.bottom() {
+            return None;
+        }
+        Some(((x - self.virtual_x) as u16 + self.output_x, (y - self.virtual_y) as u16 + self.output_y))
+    }
+
     pub fn get_output_coordinates(&self, d: &Box<DynDrawable>) -> OutputCoordinates {
         let x = if d.x() < self.virtual_x {
                 0
@@ -5,10 +5,17 @@ use crate::logging::RemoteLoggerClient;
 use lazy_static::lazy_static;
 use log::{info, LevelFilter};
 use rustupolis::engine::core::Engine;
+use rustupolis::engine::ascii_map::{export_ascii_map, import_ascii_map};
 use rustupolis::engine::keybinds::KeyBindListener;
-use rustupolis::engine::layout::Layout;
+use rustupolis::engine::snapshot::{export_html, export_svg};
+use rustupolis::engine::layout::{IssueSeverity, Layout};
+use rustupolis::engine::migrations::{migrate, LAYOUT_FORMAT_VERSION};
+use rustupolis::population::disease::{install_diseases, DiseaseRegistry};
+use rustupolis::population::dna::{install_traits, TraitRegistry};
+use rustupolis::population::cohort::PopulationModel;
+use rustupolis::population::Population;
+use rustupolis::simulation::balance::Balance;
 use rustupolis::engine::viewport::Viewport;
-use rustupolis::roads::road_graph::{Graph, Rect};
 use rustupolis::terminal::screen::CleanScreen;
 use rustupolis::threads::demo::demo_scope;
 use rustupolis::threads::engine_loop::engine_loop;
@@ -22,6 +29,7 @@ use std::{env, fs, thread};
 use std::fs::File;
 use std::path::PathBuf;
 use std::process::exit;
+use std::str::FromStr;
 use termion::input::MouseTerminal;
 use termion::raw::IntoRawMode;
 use termion::terminal_size;
@@ -34,30 +42,234 @@ lazy_static! {
 
 
 
+/// Exit if the file can't be read.
+fn read_layout_file(path: &str) -> String {
+    let pb = PathBuf::from(path);
+
+    if !pb.exists() || !pb.is_file() {
+        eprintln!("Not a file");
+        exit(1)
+    }
+
+    match fs::read_to_string(&pb) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", pb.display(), e);
+            exit(1)
+        }
+    }
+}
+
+/// Rewrite the layout file in place with the current format version.
+fn upgrade_layout(path: &str) {
+    let mut value: serde_json::Value = match serde_json::from_str(&read_layout_file(path)) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{} is not valid JSON: {}", path, e);
+            exit(1)
+        }
+    };
+
+    let original = match migrate(&mut value) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Could not upgrade {}: {}", path, e);
+            exit(1)
+        }
+    };
+
+    if original == LAYOUT_FORMAT_VERSION {
+        println!("{} is already at version {}", path, LAYOUT_FORMAT_VERSION);
+        return;
+    }
+
+    // Going through `Layout` makes sure the upgraded file can really be loaded
+    let layout: Layout = match serde_json::from_value(value) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Upgraded {} is not a valid layout: {}", path, e);
+            exit(1)
+        }
+    };
+
+    if let Err(e) = fs::write(path, serde_json::to_string_pretty(&layout).unwrap()) {
+        eprintln!("Could not write {}: {}", path, e);
+        exit(1)
+    }
+
+    println!("Upgraded {} from version {} to {}", path, original, LAYOUT_FORMAT_VERSION);
+}
+
+/// Load a JSON layout or a text map (`.map`), exit if it's not valid.
+fn load_layout(path: &str) -> Layout {
+    let loaded = if path.ends_with(".map") {
+        import_ascii_map(&read_layout_file(path))
+    } else {
+        Layout::from_json(&read_layout_file(path)).map_err(|e| e.to_string())
+    };
+
+    match loaded {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("{} is not a valid layout: {}", path, e);
+            exit(1)
+        }
+    }
+}
+
+/// Load the coefficients of the simulation, exit if the file is not valid.
+fn load_balance(path: &str) -> Balance {
+    match Balance::from_json(&read_layout_file(path)) {
+        Ok(balance) => balance,
+        Err(e) => {
+            eprintln!("{} is not a valid balance file: {}", path, e);
+            exit(1)
+        }
+    }
+}
+
+/// Replace the bundled DNA traits and diseases, exit if a file is not valid.
+fn load_genetics(traits: Option<String>, diseases: Option<String>) {
+    if let Some(path) = traits {
+        let installed = TraitRegistry::from_json(&read_layout_file(&path)).and_then(install_traits);
+        if let Err(e) = installed {
+            eprintln!("{} is not a valid traits file: {}", path, e);
+            exit(1)
+        }
+    }
+
+    if let Some(path) = diseases {
+        let installed = DiseaseRegistry::from_json(&read_layout_file(&path)).and_then(install_diseases);
+        if let Err(e) = installed {
+            eprintln!("{} is not a valid diseases file: {}", path, e);
+            exit(1)
+        }
+    }
+}
+
+/// Remove `<name> <value>` from the arguments and return the value, exit if it's missing.
+fn take_option(args: &mut Vec<String>, name: &str, usage: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == name)?;
+    let Some(value) = args.get(i + 1).cloned() else {
+        eprintln!("Usage: {} {} {} [layout]", args[0], name, usage);
+        exit(1)
+    };
+    args.drain(i..=i + 1);
+    Some(value)
+}
+
+/// Print the layout as a text map on stdout.
+fn export_map(path: &str) {
+    let layout = load_layout(path);
+
+    match export_ascii_map(&layout) {
+        Ok(map) => print!("{}", map),
+        Err(e) => {
+            eprintln!("Could not export {}: {}", path, e);
+            exit(1)
+        }
+    }
+}
+
+/// Write `<output>.svg` and `<output>.html` pictures of the layout with its starting population.
+fn snapshot(path: &str, output: &str, balance: Balance) {
+    let mut layout = load_layout(path);
+    let population = Population::new(&mut layout, balance.districts);
+    let output = PathBuf::from(output);
+
+    for (file, content) in [
+        (output.with_extension("svg"), export_svg(&layout, &population)),
+        (output.with_extension("html"), export_html(&layout, &population)),
+    ] {
+        if let Err(e) = fs::write(&file, content) {
+            eprintln!("Could not write {}: {}", file.display(), e);
+            exit(1)
+        }
+    }
+}
+
 fn main() {
     log::set_logger(LOGGER.deref())
         .map(|()| log::set_max_level(LevelFilter::Debug))
         .unwrap();
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // the data files can come before any other option
+    let balance = match take_option(&mut args, "--balance", "<balance.json>") {
+        Some(path) => load_balance(&path),
+        None => Balance::default(),
+    };
+    let traits = take_option(&mut args, "--traits", "<traits.json>");
+    let diseases = take_option(&mut args, "--diseases", "<diseases.json>");
+    load_genetics(traits, diseases);
+    let model = match take_option(&mut args, "--population-model", "<individual|cohort>") {
+        Some(name) => PopulationModel::from_str(&name).unwrap_or_else(|_| {
+            eprintln!("Unknown population model {}, use individual or cohort", name);
+            exit(1)
+        }),
+        None => PopulationModel::default(),
+    };
+
+    if args.get(1).map(|a| a.as_str()) == Some("--default-balance") {
+        println!("{}", serde_json::to_string_pretty(&Balance::default()).unwrap());
+        return;
+    }
+
+    if args.get(1).map(|a| a.as_str()) == Some("--upgrade-layout") {
+        let Some(path) = args.get(2) else {
+            eprintln!("Usage: {} --upgrade-layout <layout.json>", args[0]);
+            exit(1)
+        };
+        upgrade_layout(path);
+        return;
+    }
 
-    let (mut layout, is_empty) = if let Some(path) = args.get(1) {
-        let pb = PathBuf::from(path);
+    if args.get(1).map(|a| a.as_str()) == Some("--export-map") {
+        let Some(path) = args.get(2) else {
+            eprintln!("Usage: {} --export-map <layout.json>", args[0]);
+            exit(1)
+        };
+        export_map(path);
+        return;
+    }
 
-        if !pb.exists() || !pb.is_file() {
-            eprintln!("Not a file");
+    if args.get(1).map(|a| a.as_str()) == Some("--connectivity") {
+        let Some(path) = args.get(2) else {
+            eprintln!("Usage: {} --connectivity <layout>", args[0]);
             exit(1)
+        };
+        let layout = load_layout(path);
+        for line in layout.graph.connectivity_report().describe(&layout) {
+            println!("{}", line);
         }
+        return;
+    }
 
-        (serde_json::from_str(&fs::read_to_string(pb).unwrap()).unwrap(), false)
+    if args.get(1).map(|a| a.as_str()) == Some("--snapshot") {
+        let (Some(path), Some(output)) = (args.get(2), args.get(3)) else {
+            eprintln!("Usage: {} --snapshot <layout> <output name>", args[0]);
+            exit(1)
+        };
+        snapshot(path, output, balance);
+        return;
+    }
+
+    let (layout, is_empty) = if let Some(path) = args.get(1) {
+        (load_layout(path), false)
     } else {
         (Layout::load_empty_layout(), true)
     };
 
-    let _clear = CleanScreen::new();
+    let issues = layout.validate();
+    for issue in &issues {
+        eprintln!("{}", issue);
+    }
+    if issues.iter().any(|i| i.severity == IssueSeverity::Error) {
+        exit(1)
+    }
 
-    let mut wonder_graph = Graph::new(&layout);
-    wonder_graph.start_dfs(&layout);
+    let _clear = CleanScreen::new();
 
     //println!("{:?}",layout);
     
@@ -77,7 +289,8 @@ fn main() {
 
     let (sidebar_chan, sidebar) = sidebar(stdout.clone());
 
-    let mut engine = Engine::new(vp, stdout.clone(), sidebar_chan.clone(), layout);
+    let mut engine = Engine::new(vp, stdout.clone(), sidebar_chan.clone(), layout, balance);
+    engine.population.set_model(model);
 
     engine.refresh();
 
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use strum_macros::{Display, EnumString};
+
+use crate::engine::layout::LayoutId;
+use crate::population::disease::Disease;
+use crate::population::dna::DNA;
+use crate::population::people::{BasePeopleInfo, Mood, People, WorkLethality};
+
+/// Months of age merged in the same cohort
+const AGE_BAND_MONTHS: u32 = 12;
+/// Below this variance the binomial is drawn person by person
+const NORMAL_APPROXIMATION_VARIANCE: f64 = 9.0;
+
+/// How the people of a district are stored, chosen with `--population-model`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum PopulationModel {
+    /// One entry per person
+    #[default]
+    Individual,
+    /// The people sharing a building, a job, an age band, their DNA, mood and disease are
+    /// merged in a single entry (see `People::get_count`), witnesses stay on their own. \
+    /// Everything that happens to each person with some chance happens to a binomial share
+    /// of the cohort, so both models give the same results on average.
+    Cohort,
+}
+
+/// Number of successes out of `n` tries with the chance `p`, the normal approximation is used
+/// for the big cohorts.
+pub fn binomial(rng: &mut impl Rng, n: u32, p: f64) -> u32 {
+    let p = p.clamp(0.0, 1.0);
+    let variance = n as f64 * p * (1.0 - p);
+
+    if variance < NORMAL_APPROXIMATION_VARIANCE {
+        return (0..n).filter(|_| rng.random_bool(p)).count() as u32;
+    }
+
+    // Box-Muller
+    let (u1, u2): (f64, f64) = (rng.random_range(f64::EPSILON..1.0), rng.random());
+    let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    (n as f64 * p + normal * variance.sqrt()).round().clamp(0.0, n as f64) as u32
+}
+
+type CohortKey = (
+    Option<LayoutId>,
+    Option<LayoutId>,
+    Option<WorkLethality>,
+    u32,
+    DNA,
+    Mood,
+    Option<Disease>,
+    bool,
+);
+
+/// Merge the alive non-witnesses sharing the same cohort key, the merged age is their mean
+/// age. Dead people are kept as they are.
+pub fn compact(peoples: Vec<People>) -> Vec<People> {
+    let mut cohorts: HashMap<CohortKey, usize> = HashMap::new();
+    // total age in months of each merged entry
+    let mut ages: Vec<u64> = vec![];
+    let mut result: Vec<People> = Vec::with_capacity(peoples.len());
+
+    for people in peoples {
+        let Some(alive) = people.as_alive().filter(|_| !people.is_witness()) else {
+            ages.push(0);
+            result.push(people);
+            continue;
+        };
+
+        let key = (
+            alive.building_uuid,
+            alive.workplace_uuid,
+            alive.work_status,
+            alive.age_in_months / AGE_BAND_MONTHS,
+            alive.dna.clone(),
+            alive.mood,
+            alive.disease,
+            alive.congested_commute,
+        );
+        let months = alive.age_in_months as u64 * alive.count as u64;
+
+        match cohorts.get(&key) {
+            Some(&index) => {
+                ages[index] += months;
+                let cohort = result[index].as_alive_mut().unwrap();
+                cohort.count += alive.count;
+                cohort.age_in_months = (ages[index] / cohort.count as u64) as u32;
+            }
+            None => {
+                cohorts.insert(key, result.len());
+                ages.push(months);
+                result.push(people);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::district::{DistrictZone, PopulationDistrict};
+    use crate::simulation::balance::{BirthBalance, DeathBalance};
+    use crate::simulation::services::Services;
+    use crate::simulation::{make_pairs, update_births, update_deaths};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::mpsc::channel;
+
+    fn mean_and_variance(samples: &[u32]) -> (f64, f64) {
+        let mean = samples.iter().map(|s| *s as f64).sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|s| (*s as f64 - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        (mean, variance)
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= expected * tolerance,
+            "{} is not within {}% of {}",
+            actual,
+            tolerance * 100.0,
+            expected
+        );
+    }
+
+    fn person(age_in_months: u32, home: LayoutId) -> People {
+        let mut people = People::create_people(DNA::new(), Mood::Neutral, Some(home), false, &mut StdRng::seed_from_u64(0));
+        people.as_alive_mut().unwrap().age_in_months = age_in_months;
+        people
+    }
+
+    /// Adults of every age spread over a few buildings, the same for every call
+    fn district() -> PopulationDistrict {
+        let homes: Vec<LayoutId> = (0..4).map(|_| LayoutId::random()).collect();
+        let peoples: Vec<People> = (0..800).map(|i| person(18 * 12 + (i * 7) % (70 * 12), homes[i as usize % 4])).collect();
+
+        let mut district = PopulationDistrict {
+            id: 0,
+            num_people: peoples.len(),
+            peoples,
+            zone_type: DistrictZone::Core,
+            capacity: 1000,
+            max_num_neighbors: 0,
+            neighbors: vec![],
+            num_happiness: 0,
+            num_sick: 0,
+            working_poulation: 0,
+            food_supply: 1.0,
+            occupants: HashMap::new(),
+        };
+        district.recalcul_occupancy();
+        district
+    }
+
+    /// Mean births and deaths of one month, each month starts again from `district`
+    fn monthly_births_and_deaths(district: &PopulationDistrict, months: u32, seed: u64) -> (f64, f64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (pipe, _messages) = channel();
+        let (mut births, mut deaths) = (0, 0);
+
+        for _ in 0..months {
+            let mut month = district.clone();
+            update_births(pipe.clone(), &mut month, &Services::default(), &BirthBalance::default(), &mut 0, &mut rng, false);
+            births += month.count_alive() - district.count_alive();
+
+            let before = month.count_alive();
+            update_deaths(pipe.clone(), &mut month, &HashMap::new(), &Services::default(), &DeathBalance::default(), &mut rng, false);
+            deaths += before - month.count_alive();
+        }
+
+        (births as f64 / months as f64, deaths as f64 / months as f64)
+    }
+
+    #[test]
+    fn binomial_follows_the_binomial_law() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // drawn person by person, then with the normal approximation
+        for (n, p) in [(20, 0.3), (5000, 0.2)] {
+            let variance = n as f64 * p * (1.0 - p);
+            assert_eq!(variance < NORMAL_APPROXIMATION_VARIANCE, n == 20);
+
+            let samples: Vec<u32> = (0..20_000).map(|_| binomial(&mut rng, n, p)).collect();
+            let (mean, sample_variance) = mean_and_variance(&samples);
+            assert_close(mean, n as f64 * p, 0.02);
+            assert_close(sample_variance, variance, 0.05);
+            assert!(samples.iter().all(|s| *s <= n));
+        }
+    }
+
+    #[test]
+    fn compact_keeps_counts_and_mean_age() {
+        let home = LayoutId::random();
+        let peoples = vec![
+            person(24, home),
+            person(30, home),
+            person(35, home),
+            // another age band
+            person(40, home),
+            // witnesses stay on their own
+            People::create_people(DNA::new(), Mood::Neutral, Some(home), true, &mut StdRng::seed_from_u64(0)),
+        ];
+
+        let compacted = compact(peoples);
+
+        assert_eq!(compacted.len(), 3);
+        assert_eq!(compacted.iter().map(|p| p.get_count()).sum::<u32>(), 5);
+        let first = compacted[0].as_alive().unwrap();
+        assert_eq!((first.count, first.age_in_months), (3, (24 + 30 + 35) / 3));
+        assert_eq!(compacted[1].as_alive().unwrap().age_in_months, 40);
+        assert!(compacted[2].is_witness());
+    }
+
+    #[test]
+    fn both_models_have_the_same_births_and_deaths() {
+        let individuals = district();
+        let mut cohorts = individuals.clone();
+        cohorts.compact();
+        assert!(cohorts.peoples.len() < individuals.peoples.len() / 2);
+        assert_eq!(cohorts.count_alive(), individuals.count_alive());
+
+        let (births, deaths) = monthly_births_and_deaths(&individuals, 200, 42);
+        let (cohort_births, cohort_deaths) = monthly_births_and_deaths(&cohorts, 200, 42);
+
+        assert!(births > 1.0 && deaths > 1.0, "{} births and {} deaths are too few to compare", births, deaths);
+        assert_close(cohort_births, births, 0.05);
+        assert_close(cohort_deaths, deaths, 0.05);
+    }
+
+    #[test]
+    fn make_pairs_uses_each_member_once() {
+        let mut cohorts = district();
+        cohorts.compact();
+        let adults = || cohorts.peoples.iter().enumerate().collect::<Vec<_>>();
+
+        let pairs = make_pairs(adults(), &mut StdRng::seed_from_u64(3));
+        assert_eq!(pairs, make_pairs(adults(), &mut StdRng::seed_from_u64(3)));
+
+        let mut partners = vec![0; cohorts.peoples.len()];
+        for ((i, j), couples) in &pairs {
+            assert_eq!(cohorts.peoples[*i].get_building_uuid(), cohorts.peoples[*j].get_building_uuid());
+            partners[*i] += couples;
+            partners[*j] += couples;
+        }
+        assert!(partners.iter().zip(&cohorts.peoples).all(|(used, p)| *used <= p.get_count()));
+        // at most one person left alone per building
+        let couples: u32 = pairs.iter().map(|(_, couples)| couples).sum();
+        assert!(couples * 2 >= cohorts.count_alive() as u32 - 4);
+    }
+}
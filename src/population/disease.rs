@@ -1,26 +1,137 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DiseaseName {
-    Bacteria, // should probably be real name and not diseases type
-              //...
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+static DISEASES: OnceLock<DiseaseRegistry> = OnceLock::new();
+
+/// Diseases loaded with `install_diseases`, or the bundled ones (`initial_data/diseases.json`).
+pub fn diseases() -> &'static DiseaseRegistry {
+    DISEASES.get_or_init(|| DiseaseRegistry::from_json(include_str!("../initial_data/diseases.json")).unwrap())
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Replace the bundled diseases, must be called before the simulation starts.
+pub fn install_diseases(registry: DiseaseRegistry) -> Result<(), String> {
+    DISEASES.set(registry).map_err(|_| "The diseases are already loaded".to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DiseaseLethality {
     Deadly,
     Moderate,
     Low,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DiseaseContagionRate {
     High,
-    Mederate,
+    Moderate,
     Low,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl DiseaseContagionRate {
+    /// Chance each month to give the disease to each person living with a sick one
+    pub fn chance(&self) -> f64 {
+        match self {
+            DiseaseContagionRate::High => 0.2,
+            DiseaseContagionRate::Moderate => 0.1,
+            DiseaseContagionRate::Low => 0.03,
+        }
+    }
+}
+
+/// Index of a disease in the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiseaseId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiseaseDef {
+    pub name: String,
+    pub lethality: DiseaseLethality,
+    pub contagion: DiseaseContagionRate,
+    /// Months before the sick get better
+    pub duration: u32,
+    /// Chance each month for the disease to appear in each district
+    pub outbreak: f64,
+    /// Youngest age that can catch it, in years
+    #[serde(default)]
+    pub min_age: u32,
+    /// Oldest age that can catch it, in years, None for everyone
+    #[serde(default)]
+    pub max_age: Option<u32>,
+}
+
+impl DiseaseDef {
+    pub fn affects(&self, age: u32) -> bool {
+        age >= self.min_age && self.max_age.is_none_or(|max| age <= max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiseaseRegistry {
+    diseases: Vec<DiseaseDef>,
+}
+
+impl DiseaseRegistry {
+    /// A JSON list of `DiseaseDef`, see `DiseaseRegistry::new` for what is refused.
+    pub fn from_json(json: &str) -> Result<DiseaseRegistry, String> {
+        let diseases: Vec<DiseaseDef> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        DiseaseRegistry::new(diseases)
+    }
+
+    /// The names must be unique, the outbreak a chance between 0 and 1, the duration at least a month
+    /// and the age range not empty.
+    pub fn new(diseases: Vec<DiseaseDef>) -> Result<DiseaseRegistry, String> {
+        for (i, def) in diseases.iter().enumerate() {
+            if diseases[..i].iter().any(|d| d.name == def.name) {
+                return Err(format!("The disease {} is defined twice", def.name));
+            }
+            if !(0.0..=1.0).contains(&def.outbreak) {
+                return Err(format!("The outbreak of the disease {} is not between 0 and 1", def.name));
+            }
+            if def.duration == 0 {
+                return Err(format!("The disease {} lasts 0 months", def.name));
+            }
+            if def.max_age.is_some_and(|max| def.min_age > max) {
+                return Err(format!("The min_age of the disease {} is above its max_age", def.name));
+            }
+        }
+
+        Ok(DiseaseRegistry { diseases })
+    }
+
+    pub fn get(&self, id: DiseaseId) -> &DiseaseDef {
+        &self.diseases[id.0]
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = DiseaseId> {
+        (0..self.diseases.len()).map(DiseaseId)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Disease {
-    pub name: DiseaseName,
+    pub kind: DiseaseId,
     pub lethality: DiseaseLethality,
     pub contagion: DiseaseContagionRate,
-}
\ No newline at end of file
+    pub months_left: u32,
+}
+
+impl Disease {
+    /// Freshly caught, for the whole duration of the disease
+    pub fn catch(kind: DiseaseId) -> Disease {
+        let def = diseases().get(kind);
+        Disease {
+            kind,
+            lethality: def.lethality,
+            contagion: def.contagion,
+            months_left: def.duration,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        &diseases().get(self.kind).name
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
+
 use crate::{engine::layout::BuildingType, population::*};
-use rand::{rng, seq::IndexedRandom};
-use strum_macros::EnumString;
+use rand::{rng, seq::IndexedRandom, Rng};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum DistrictZone {
     Core,
     Residentials,
@@ -10,7 +15,7 @@ pub enum DistrictZone {
     Industrials,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PopulationDistrict {
     pub id: usize,
     pub peoples: Vec<People>,
@@ -24,11 +29,15 @@ pub struct PopulationDistrict {
     pub max_num_neighbors: usize,
     pub neighbors: Vec<usize>,
 
-    /// Use get_percentage instead
-    pub num_happiness: u16,
+    /// Use get_percentage instead, sum of the moods so it can be negative
+    pub num_happiness: i32,
     /// Use get_percentage instead
     pub num_sick: u16, // use
     pub working_poulation: u16,
+    /// Food produced last month over the food needed, people starve below 1.0
+    pub food_supply: f32,
+    /// Use get_occupants instead, alive people living in each building
+    pub occupants: HashMap<LayoutId, usize>,
 }
 
 impl PopulationDistrict {
@@ -36,7 +45,89 @@ impl PopulationDistrict {
         self.peoples
             .iter()
             .filter(|p| p.get_legal_state() == find_by)
-            .count()
+            .map(|p| p.get_count() as usize)
+            .sum()
+    }
+
+    /// Alive people, counting every member of the cohorts
+    pub fn count_alive(&self) -> usize {
+        self.peoples.iter().filter(|p| p.as_alive().is_some()).map(|p| p.get_count() as usize).sum()
+    }
+
+    /// Alive people living in the building, without scanning the district
+    pub fn get_occupants(&self, building: &LayoutId) -> usize {
+        self.occupants.get(building).copied().unwrap_or(0)
+    }
+
+    /// Merge the people into cohorts, see `PopulationModel::Cohort`
+    pub fn compact(&mut self) {
+        self.peoples = cohort::compact(std::mem::take(&mut self.peoples));
+    }
+
+    /// Apply `f` to each alive person with the `chance` of that person, a binomial share of each
+    /// cohort is split off to be changed (see `People::with_members`). \
+    /// Return the number of people changed.
+    pub fn affect_alive(
+        &mut self,
+        rng: &mut impl Rng,
+        chance: impl Fn(&AlivePerson) -> f64,
+        mut f: impl FnMut(&mut People),
+    ) -> u32 {
+        let mut affected = 0;
+        let mut parts = vec![];
+        // (from, to, amount) of the people who moved out, or died
+        let mut moves = vec![];
+        for people in self.peoples.iter_mut() {
+            let Some(alive) = people.as_alive() else {
+                continue;
+            };
+            let chance = chance(alive);
+            if chance <= 0.0 {
+                continue;
+            }
+
+            let home = alive.building_uuid;
+            let amount = cohort::binomial(rng, alive.count, chance);
+            affected += amount;
+
+            let mut new_home = home;
+            parts.extend(people.with_members(amount, |p| {
+                f(p);
+                new_home = p.as_alive().and_then(|a| a.building_uuid);
+            }));
+            moves.push((home, new_home, amount as usize));
+        }
+        self.peoples.append(&mut parts);
+        for (from, to, amount) in moves {
+            self.move_occupants(from, to, amount);
+        }
+
+        affected
+    }
+
+    pub fn recalcul_occupancy(&mut self) {
+        self.occupants = HashMap::new();
+        Self::aux_occupancy(&mut self.occupants, &self.peoples);
+    }
+    /// Will just add the people to the buildings
+    pub fn update_occupancy(&mut self, peoples: &[People]) {
+        Self::aux_occupancy(&mut self.occupants, peoples);
+    }
+    /// `amount` people left their home `from` for `to`, None being the street or the grave
+    pub fn move_occupants(&mut self, from: Option<LayoutId>, to: Option<LayoutId>, amount: usize) {
+        if from == to {
+            return;
+        }
+        if let Some(count) = from.and_then(|id| self.occupants.get_mut(&id)) {
+            *count = count.saturating_sub(amount);
+        }
+        if let Some(id) = to {
+            *self.occupants.entry(id).or_default() += amount;
+        }
+    }
+    /// Nobody lives in the building anymore, see `Population::evict_building`
+    pub fn clear_occupancy(&mut self, building: &LayoutId) {
+        self.occupants.remove(building);
     }
 
     pub fn recalcul_happiness(&mut self) {
@@ -52,7 +143,7 @@ impl PopulationDistrict {
     }
     /// Will just add the number to the field
     pub fn update_sickness(&mut self, peoples: &Vec<People>) {
-        self.num_happiness += Self::aux_sickness(peoples);
+        self.num_sick += Self::aux_sickness(peoples);
     }
 
     pub fn recalcul_working_population(&mut self) {
@@ -65,7 +156,7 @@ impl PopulationDistrict {
 
     /// Give the correct happiness percentage
     pub fn get_happiness_percentage(&self) -> f32 {
-        (((self.num_people / 2) as u16 + self.num_happiness) as f32 / self.num_people as f32)
+        (((self.num_people / 2) as i32 + self.num_happiness) as f32 / self.num_people as f32)
             .clamp(0f32, 1f32)
     }
 
@@ -74,11 +165,12 @@ impl PopulationDistrict {
     }
 
     pub fn add_peoples(&mut self, peoples: &mut Vec<People>) {
-        self.num_people += peoples.len();
+        self.num_people += peoples.iter().map(|p| p.get_count() as usize).sum::<usize>();
 
         self.update_happiness(peoples);
         self.update_sickness(peoples);
         self.update_working_population(peoples);
+        self.update_occupancy(peoples);
 
         self.peoples.append(peoples);
     }
@@ -94,37 +186,44 @@ impl PopulationDistrict {
             .filter(|people| people.get_legal_state() != PeopleLegalState::Child)
         {
             if let Some(people) = people.as_alive_mut() {
-                people.building_uuid = Some(
-                    buildings
-                        .iter()
-                        .filter(|b| b.get_building_type() != BuildingType::EmptySpace)
-                        .collect::<Vec<_>>()
-                        .choose(&mut rng)
-                        .unwrap()
-                        .get_building_uuid(),
-                );
+                // homeless if the district has nowhere to live yet
+                people.building_uuid = buildings
+                    .iter()
+                    .filter(|b| b.get_housing_capacity() > 0)
+                    .collect::<Vec<_>>()
+                    .choose(&mut rng)
+                    .map(|b| b.get_building_uuid());
             }
         }
+        self.recalcul_occupancy();
     }
 
     // ----- not public -----
 
-    fn aux_happiness(peoples: &Vec<People>) -> u16 {
+    fn aux_happiness(peoples: &Vec<People>) -> i32 {
         let mut res = 0;
         for people in peoples {
             match people {
-                People::Alive(AlivePerson { mood, .. }) => res += *mood as u16,
-                People::Dead { .. } => res -= 1,
+                People::Alive(AlivePerson { mood, count, .. }) => res += *mood as i32 * *count as i32,
+                People::Dead(DeadPerson { count, .. }) => res -= *count as i32,
             }
         }
         res
     }
 
+    fn aux_occupancy(occupants: &mut HashMap<LayoutId, usize>, peoples: &[People]) {
+        for alive in peoples.iter().filter_map(|p| p.as_alive()) {
+            if let Some(home) = alive.building_uuid {
+                *occupants.entry(home).or_default() += alive.count as usize;
+            }
+        }
+    }
+
     fn aux_sickness(peoples: &Vec<People>) -> u16 {
         let mut res = 0;
         for people in peoples {
             match people {
-                People::Alive(AlivePerson { disease, .. }) if { *disease != None } => res += 1,
+                People::Alive(AlivePerson { disease, count, .. }) if { *disease != None } => res += *count as u16,
                 _ => (),
             }
         }
@@ -136,7 +235,7 @@ impl PopulationDistrict {
         let mut res = 0;
         for people in peoples {
             match people {
-                People::Alive(AlivePerson { work_status, .. }) if work_status.is_some() => res += 1,
+                People::Alive(AlivePerson { work_status, count, .. }) if work_status.is_some() => res += *count as u16,
                 _ => (),
             }
         }
@@ -1,169 +1,339 @@
 use std::fmt;
-use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use std::sync::OnceLock;
 
-use strum::IntoEnumIterator;
-use strum_macros::{EnumCount, EnumIter};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumCount)]
-pub enum DNAFlags {
-    HealthyCarrier = 1 << 0, // can have a disease but cannot die from it
-    FastMetabolism = 1 << 1, // more food but age faster
-    SlowMetabolism = 1 << 2, // ^ oposite
-    DiseaseResistant = 1 << 3,
-    ProneToDisease = 1 << 4,
-    StrongImmune = 1 << 5,
-    WeakImmune = 1 << 6,
-    HighFertility = 1 << 7,
-    LowFertility = 1 << 8,
-    Longevity = 1 << 9,
-    ShortLifespan = 1 << 10,
-    StrongPhysique = 1 << 11,  // Higher chance of surviving injuries
-    FragilePhysique = 1 << 12, // ^ oposite
-    KeenSenses = 1 << 13,      // Better at finding food or avoiding monsters
-    PoorSenses = 1 << 14,      // ^ oposite
-    ColdResistant = 1 << 15,
-    HeatResistant = 1 << 16,
-    Aggressive = 1 << 17,
-    Docile = 1 << 18,
-    UnstableDNA = 1 << 19,      // MORE MUTATIONS
-}
+static TRAITS: OnceLock<TraitRegistry> = OnceLock::new();
 
-impl BitAnd for DNAFlags {
-    type Output = u32;
+/// Traits loaded with `install_traits`, or the bundled ones (`initial_data/traits.json`).
+pub fn traits() -> &'static TraitRegistry {
+    TRAITS.get_or_init(|| TraitRegistry::from_json(include_str!("../initial_data/traits.json")).unwrap())
+}
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        self as u32 & rhs as u32
-    }
+/// Replace the bundled traits, must be called before the first `DNA` is made.
+pub fn install_traits(registry: TraitRegistry) -> Result<(), String> {
+    TRAITS.set(registry).map_err(|_| "The DNA traits are already loaded".to_string())
 }
 
-impl BitOr for DNAFlags {
-    type Output = u32;
+/// Index of a trait in the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TraitId(pub usize);
+
+/// How a trait changes the life of the people carrying it, the neutral values are the defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraitEffects {
+    /// Multiplies the chance to have children
+    pub fertility: f64,
+    /// Years added to the age people die of old age
+    pub lifespan: f64,
+    /// Multiplies the chance to die
+    pub frailty: f64,
+    /// Added to the mutation chance of the children
+    pub mutation: f64,
+    /// Chance to run away from a monster attack
+    pub monster_escape: f64,
+    /// Multiplies the chance to catch a disease
+    pub infection: f64,
+    /// Multiplies the extra deaths caused by a disease, 0 means the disease can't kill
+    pub sickness: f64,
+}
 
-    fn bitor(self, rhs: Self) -> Self::Output {
-        self as u32 | rhs as u32
+impl Default for TraitEffects {
+    fn default() -> Self {
+        TraitEffects {
+            fertility: 1.0,
+            lifespan: 0.0,
+            frailty: 1.0,
+            mutation: 0.0,
+            monster_escape: 0.0,
+            infection: 1.0,
+            sickness: 1.0,
+        }
     }
 }
 
-impl BitXor for DNAFlags {
-    type Output = u32;
+impl TraitEffects {
+    /// Effects of a partly expressed trait, `expression` goes from 0 (neutral) to 1 (full effects)
+    pub fn scaled(&self, expression: f64) -> TraitEffects {
+        TraitEffects {
+            fertility: self.fertility.powf(expression),
+            lifespan: self.lifespan * expression,
+            frailty: self.frailty.powf(expression),
+            mutation: self.mutation * expression,
+            monster_escape: self.monster_escape * expression,
+            infection: self.infection.powf(expression),
+            sickness: 1.0 + (self.sickness - 1.0) * expression,
+        }
+    }
 
-    fn bitxor(self, rhs: Self) -> Self::Output {
-        self as u32 ^ rhs as u32
+    /// Effects of having both sets of traits
+    pub fn combine(&self, other: &TraitEffects) -> TraitEffects {
+        TraitEffects {
+            fertility: self.fertility * other.fertility,
+            lifespan: self.lifespan + other.lifespan,
+            frailty: self.frailty * other.frailty,
+            mutation: self.mutation + other.mutation,
+            monster_escape: 1.0 - (1.0 - self.monster_escape) * (1.0 - other.monster_escape),
+            infection: self.infection * other.infection,
+            sickness: self.sickness * other.sickness,
+        }
     }
 }
 
-impl Not for DNAFlags {
-    type Output = u32;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dominance {
+    /// Expressed with a single allele
+    #[default]
+    Dominant,
+    /// Expressed only with both alleles, the carriers can pass it on without showing it
+    Recessive,
+    /// Half expressed with a single allele, see `DNA::expression`
+    Codominant,
+}
 
-    fn not(self) -> Self::Output {
-        !(self as u32)
-    }
+/// Unknown fields are refused so old or mistyped trait files don't silently lose a setting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraitDef {
+    pub name: String,
+    /// How the two alleles of the trait are expressed
+    #[serde(default)]
+    pub dominance: Dominance,
+    /// Multiplies the mutation chance of this trait
+    #[serde(default = "default_mutation_rate")]
+    pub mutation_rate: f64,
+    /// Names of the traits an allele can't carry along with this one, gaining one removes the others
+    #[serde(default)]
+    pub exclusive_with: Vec<String>,
+    #[serde(default)]
+    pub effects: TraitEffects,
 }
 
-impl From<DNAFlags> for u32 {
-    fn from(value: DNAFlags) -> Self {
-        value as u32
-    }
+fn default_mutation_rate() -> f64 {
+    1.0
 }
 
-// the wrapper
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct DNA {
-    traits: u32,
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitRegistry {
+    traits: Vec<TraitDef>,
+    /// Exclusive traits of each trait, both ways
+    exclusions: Vec<Vec<TraitId>>,
 }
 
-impl DNA {
-    pub fn new() -> Self {
-        DNA { traits: 0 }
+impl TraitRegistry {
+    /// A JSON list of `TraitDef`, the names must be unique and the exclusions must name known traits.
+    pub fn from_json(json: &str) -> Result<TraitRegistry, String> {
+        let traits: Vec<TraitDef> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        TraitRegistry::new(traits)
     }
 
-    pub fn from_flag(flags: u32) -> Self {
-        DNA {
-            traits: flags,
+    pub fn new(traits: Vec<TraitDef>) -> Result<TraitRegistry, String> {
+        let mut exclusions = vec![vec![]; traits.len()];
+
+        for (i, def) in traits.iter().enumerate() {
+            if traits[..i].iter().any(|t| t.name == def.name) {
+                return Err(format!("The trait {} is defined twice", def.name));
+            }
+
+            for other in &def.exclusive_with {
+                let Some(j) = traits.iter().position(|t| t.name == *other) else {
+                    return Err(format!("The trait {} is exclusive with an unknown trait {}", def.name, other));
+                };
+                for (a, b) in [(i, j), (j, i)] {
+                    if a != b && !exclusions[a].contains(&TraitId(b)) {
+                        exclusions[a].push(TraitId(b));
+                    }
+                }
+            }
         }
+
+        Ok(TraitRegistry { traits, exclusions })
     }
 
-    pub fn add_flag(&mut self, flag: u32) {
-        self.traits |= flag;
+    pub fn len(&self) -> usize {
+        self.traits.len()
     }
 
-    pub fn remove(&mut self, flags: DNAFlags) {
-        self.traits &= !(flags as u32);
+    pub fn is_empty(&self) -> bool {
+        self.traits.is_empty()
     }
 
-    pub fn contains(&self, flags: DNAFlags) -> bool {
-        (self.traits & flags as u32) != 0
+    pub fn get(&self, id: TraitId) -> &TraitDef {
+        &self.traits[id.0]
     }
-}
 
-impl BitOrAssign for DNA {
-    fn bitor_assign(&mut self, rhs: Self) {
-        self.traits |= rhs.traits
+    pub fn find(&self, name: &str) -> Option<TraitId> {
+        self.traits.iter().position(|t| t.name == name).map(TraitId)
     }
-}
 
-impl BitAndAssign for DNA {
-    fn bitand_assign(&mut self, rhs: Self) {
-        self.traits &= rhs.traits
+    pub fn ids(&self) -> impl Iterator<Item = TraitId> {
+        (0..self.traits.len()).map(TraitId)
     }
-}
 
-impl BitXorAssign for DNA {
-    fn bitxor_assign(&mut self, rhs: Self) {
-        self.traits ^= rhs.traits
+    pub fn exclusive_with(&self, id: TraitId) -> &[TraitId] {
+        &self.exclusions[id.0]
     }
 }
 
-impl BitAnd for DNA {
-    type Output = Self;
+/// One allele per trait of the registry, set when the allele carries the trait
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Alleles {
+    bits: Vec<u64>,
+}
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        DNA {
-            traits: self.traits & rhs.traits
+impl Alleles {
+    fn new() -> Self {
+        Alleles {
+            bits: vec![0; traits().len().div_ceil(64)],
         }
     }
-}
 
-impl BitOr for DNA {
-    type Output = Self;
+    pub fn contains(&self, id: TraitId) -> bool {
+        self.bits[id.0 / 64] & (1 << (id.0 % 64)) != 0
+    }
 
-    fn bitor(self, rhs: Self) -> Self::Output {
-        DNA {
-            traits: self.traits | rhs.traits
+    /// Set the allele and clear the ones of the traits exclusive with it
+    fn insert(&mut self, id: TraitId) {
+        for other in traits().exclusive_with(id) {
+            self.remove(*other);
+        }
+        self.bits[id.0 / 64] |= 1 << (id.0 % 64);
+    }
+
+    fn remove(&mut self, id: TraitId) {
+        self.bits[id.0 / 64] &= !(1 << (id.0 % 64));
+    }
+
+    fn toggle(&mut self, id: TraitId) {
+        match self.contains(id) {
+            true => self.remove(id),
+            false => self.insert(id),
         }
     }
-    
 }
 
-impl BitXor for DNA {
-    type Output = Self;
+/// Diploid genome, two alleles for each trait of the registry (see `traits()`). \
+/// The genotype is what the alleles carry, the phenotype is what shows depending on the
+/// `Dominance` of each trait.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DNA {
+    alleles: [Alleles; 2],
+}
+
+impl Default for DNA {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    fn bitxor(self, rhs: Self) -> Self::Output {
+impl DNA {
+    /// No trait
+    pub fn new() -> Self {
         DNA {
-            traits: self.traits ^ rhs.traits
+            alleles: [Alleles::new(), Alleles::new()],
         }
     }
-}
 
-impl Not for DNA {
-    type Output = Self;
+    /// Both alleles of every trait, expressed whatever their dominance
+    pub fn from_traits(ids: &[TraitId]) -> Self {
+        let mut dna = DNA::new();
+        for id in ids {
+            dna.insert(*id);
+        }
+        dna
+    }
 
-    fn not(self) -> Self::Output {
+    /// One allele from each parent, see `DNA::gamete`
+    pub fn from_gametes(gamete1: Alleles, gamete2: Alleles) -> Self {
         DNA {
-            traits: !self.traits
+            alleles: [gamete1, gamete2],
+        }
+    }
+
+    /// Is the trait expressed
+    pub fn contains(&self, id: TraitId) -> bool {
+        self.expression(id) > 0.0
+    }
+
+    /// Does at least one allele carry the trait, expressed or not
+    pub fn carries(&self, id: TraitId) -> bool {
+        self.alleles.iter().any(|a| a.contains(id))
+    }
+
+    /// How much the trait shows, 0.5 for a codominant trait carried by a single allele
+    pub fn expression(&self, id: TraitId) -> f64 {
+        let count = self.alleles.iter().filter(|a| a.contains(id)).count();
+        match (traits().get(id).dominance, count) {
+            (_, 0) => 0.0,
+            (_, 2) => 1.0,
+            (Dominance::Dominant, _) => 1.0,
+            (Dominance::Recessive, _) => 0.0,
+            (Dominance::Codominant, _) => 0.5,
         }
     }
+
+    /// Both alleles carry the trait
+    pub fn insert(&mut self, id: TraitId) {
+        for alleles in self.alleles.iter_mut() {
+            alleles.insert(id);
+        }
+    }
+
+    /// Only one allele carries the trait
+    pub fn insert_allele(&mut self, id: TraitId) {
+        self.alleles[0].insert(id);
+        self.alleles[1].remove(id);
+    }
+
+    /// Flip the trait on one of the alleles
+    pub fn toggle_allele(&mut self, id: TraitId, allele: usize) {
+        self.alleles[allele].toggle(id);
+    }
+
+    /// The allele given to a child, picked from either side for each trait
+    pub fn gamete(&self, rng: &mut impl Rng) -> Alleles {
+        let mut gamete = Alleles::new();
+        for id in traits().ids() {
+            if self.alleles[rng.random_range(0..2)].contains(id) {
+                gamete.insert(id);
+            }
+        }
+        gamete
+    }
+
+    /// Expressed traits
+    pub fn iter(&self) -> impl Iterator<Item = TraitId> + '_ {
+        traits().ids().filter(|id| self.contains(*id))
+    }
+
+    /// Effects of all the expressed traits together
+    pub fn effects(&self) -> TraitEffects {
+        self.iter().fold(TraitEffects::default(), |effects, id| {
+            effects.combine(&traits().get(id).effects.scaled(self.expression(id)))
+        })
+    }
+
+    /// Carried traits with their alleles, `+/+` for both and `+/-` for one
+    pub fn genotype(&self) -> Vec<String> {
+        traits()
+            .ids()
+            .filter(|id| self.carries(*id))
+            .map(|id| {
+                let both = self.alleles.iter().all(|a| a.contains(id));
+                format!("{} {}", traits().get(id).name, if both { "+/+" } else { "+/-" })
+            })
+            .collect()
+    }
 }
 
-// pretty debug
+// pretty debug, phenotype then genotype
 impl fmt::Debug for DNA {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let active_traits: Vec<String> = DNAFlags::iter()
-            .filter(|&flag| self.contains(flag))
-            .map(|flag| format!("{:?}", flag))
-            .collect(); // collect my beloved
+        let active_traits: Vec<&str> = self.iter().map(|id| traits().get(id).name.as_str()).collect(); // collect my beloved
 
-        write!(f, "{}", active_traits.join(" | "))
+        write!(f, "{} ({})", active_traits.join(" | "), self.genotype().join(", "))
     }
 }
@@ -1,29 +1,50 @@
+pub mod cohort;
 pub mod disease;
 pub mod district;
 pub mod dna;
 pub mod people;
 
+use std::collections::{HashMap, HashSet};
+
 use district::*;
 use people::*;
 
-use crate::engine::layout::{Building, Layout};
+use crate::engine::layout::{Building, BuildingType, Layout, LayoutId};
+use cohort::PopulationModel;
+use crate::simulation::balance::DistrictBalance;
 
 #[derive(Debug)]
 pub struct Population {
     pub num_districts: usize,
     districts: Vec<PopulationDistrict>,
+    /// Capacities and links of the new districts
+    balance: DistrictBalance,
+    pub model: PopulationModel,
+    /// Goes up with every change to the districts, see `swap_districts`
+    generation: u64,
 }
 
 impl Population {
-    /// Will also add an empty Core district.
-    pub fn new(layout: &mut Layout) -> Population {
+    /// Will add one district for each zone of the layout, or a single Core district if the
+    /// layout has none.
+    pub fn new(layout: &mut Layout, balance: DistrictBalance) -> Population {
         let mut pop = Population {
             num_districts: 0,
             districts: Vec::new(),
+            balance,
+            model: PopulationModel::default(),
+            generation: 0,
+        };
+
+        let zones = match layout.districts.is_empty() {
+            true => vec![DistrictZone::Core],
+            false => layout.districts.clone(),
         };
 
-        pop.add_district(100, DistrictZone::Core, 2);
-        pop.get_district_mut(0).unwrap().update_building_occupation(layout);
+        for zone in zones {
+            let id = pop.add_district(pop.balance.starting_population, zone, pop.balance.starting_witnesses);
+            pop.get_district_mut(id).unwrap().update_building_occupation(layout);
+        }
 
         pop
     }
@@ -35,6 +56,7 @@ impl Population {
     }
 
     pub fn get_district_mut(&mut self, id: usize) -> Option<&mut PopulationDistrict> {
+        self.generation += 1;
         self.districts.get_mut(id)
     }
 
@@ -52,6 +74,7 @@ impl Population {
 
     /// Should not panic.
     pub fn get_core_district_mut(&mut self) -> &mut PopulationDistrict {
+        self.generation += 1;
         match self
             .districts
             .iter_mut()
@@ -82,12 +105,43 @@ impl Population {
             .collect() // collect my beloved
     }
 
+    // ----- SET -----
+
+    /// Copy of the districts to update them outside of the engine, see `swap_districts`
+    pub fn snapshot_districts(&self) -> Vec<PopulationDistrict> {
+        self.districts.clone()
+    }
+
+    /// Changes whenever the districts may have changed, a snapshot taken at the same generation is
+    /// still up to date.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Put back the districts of `snapshot_districts`, only if the `generation` is still the one
+    /// of the snapshot since the changes made in the meantime would be lost.
+    pub fn swap_districts(&mut self, districts: Vec<PopulationDistrict>) {
+        self.generation += 1;
+        self.districts = districts;
+    }
+
+    /// Switch the way the people are stored, with `PopulationModel::Cohort` the districts are
+    /// merged right away and then after every month.
+    pub fn set_model(&mut self, model: PopulationModel) {
+        self.generation += 1;
+        self.model = model;
+        if model == PopulationModel::Cohort {
+            self.districts.iter_mut().for_each(|d| d.compact());
+        }
+    }
+
     // ----- ADD -----
 
     pub fn add_district(&mut self, starting_population: u8, district_zone: DistrictZone, amount_of_witness: u8) -> usize {
+        self.generation += 1;
         self.districts.push(self.setup_district(
             district_zone,
-            People::create_random_population(starting_population, amount_of_witness),
+            People::create_random_population(starting_population, amount_of_witness, &mut rand::rng()),
         ));
 
         self.num_districts += 1;
@@ -101,7 +155,7 @@ impl Population {
     pub fn add_peoples(&mut self, amount: u16, target_district: Option<usize>) {
         let mut vec = vec![];
         for _ in 0..amount {
-            vec.push(People::create_random_people(true, 1, false));
+            vec.push(People::create_random_people(true, 1, false, &mut rand::rng()));
         }
 
         match target_district {
@@ -113,6 +167,44 @@ impl Population {
         }
     }
 
+    // ----- REMOVE -----
+
+    /// Put everyone living in this building on the street (`building_uuid = None`), people working there lose their job. \
+    /// Return the number of evicted peoples and the names of the witnesses among them.
+    pub fn evict_building(&mut self, building_id: &LayoutId) -> (usize, Vec<String>) {
+        let mut count = 0;
+        let mut witnesses = vec![];
+        self.generation += 1;
+
+        for district in self.districts.iter_mut() {
+            for people in district
+                .peoples
+                .iter_mut()
+                .filter_map(|p| p.as_alive_mut())
+                .filter(|p| p.building_uuid == Some(*building_id) || p.workplace_uuid == Some(*building_id))
+            {
+                // losing your job is not as bad as losing your home
+                if people.workplace_uuid == Some(*building_id) {
+                    people.workplace_uuid = None;
+                    people.work_status = None;
+                    if people.building_uuid != Some(*building_id) {
+                        continue;
+                    }
+                }
+
+                people.building_uuid = None;
+                count += people.count as usize;
+
+                if let Some(name) = people.get_witness_name() {
+                    witnesses.push(name.clone());
+                }
+            }
+            district.clear_occupancy(building_id);
+        }
+
+        (count, witnesses)
+    }
+
     pub fn get_core_buildings_mut(&self, layout: &'static mut Layout) -> Vec<&mut Building> {
         layout.get_buildings_district_mut(0)
     }
@@ -130,6 +222,7 @@ impl Population {
             && self.districts[a].neighbors.len() < self.districts[a].max_num_neighbors  // to skip if we already have max_neighbors
             && self.districts[b].neighbors.len() < self.districts[b].max_num_neighbors
         {
+            self.generation += 1;
             self.districts[a].neighbors.push(b);
             self.districts[b].neighbors.push(a);
 
@@ -153,19 +246,8 @@ impl Population {
     }
 
     fn setup_district(&self, district_zone: DistrictZone, peoples: Vec<People>) -> PopulationDistrict {
-        let capacity = match district_zone {
-            DistrictZone::Core => 50,
-            DistrictZone::Industrials => 75,
-            DistrictZone::Residentials => 100,
-            DistrictZone::Slums => 150,
-        };
-
-        let max_num_links = match district_zone {
-            DistrictZone::Core => 4,
-            DistrictZone::Industrials => 2,
-            DistrictZone::Residentials => 3,
-            DistrictZone::Slums => 1,
-        };
+        let capacity = self.balance.capacity.get(district_zone);
+        let max_num_links = self.balance.max_links.get(district_zone);
 
         let mut district = PopulationDistrict {
             id: self.num_districts,
@@ -176,12 +258,15 @@ impl Population {
             num_happiness: 0,
             num_sick: 0,
             working_poulation: 0,
+            food_supply: 1.0,
+            occupants: HashMap::new(),
             capacity,
             max_num_neighbors: max_num_links,
         };
 
         district.recalcul_happiness();
         district.recalcul_sickness();
+        district.recalcul_occupancy();
         district.recalcul_working_population();
 
         district
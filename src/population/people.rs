@@ -1,10 +1,8 @@
 use crate::engine::layout::{Building, LayoutId};
 use crate::population::disease::*;
 use crate::population::dna::*;
-use rand::random_range;
-use rand::rng;
 use rand::seq::IndexedRandom;
-use strum::EnumCount;
+use rand::Rng;
 
 pub const BRITISH_NAMES: &[&str] = &[
     // Why british? Because.
@@ -22,10 +20,11 @@ pub enum CauseOfDeath {
     WorkAccident,
     EatenByMonster,
     Poverty,
+    Fire,
 }
 
 #[repr(i8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Mood {
     Zealot = 2, // see the Mayor as their god and so won't notice the state they are in.
     Happy = 1,
@@ -34,7 +33,7 @@ pub enum Mood {
     Angry = -2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum WorkLethality {
     SafeJob,
     MediumRisks,
@@ -64,6 +63,16 @@ impl Mood {
             _ => Mood::Neutral,
         }
     }
+
+    /// One step less happy, angry people stay angry.
+    pub fn worse(&self) -> Self {
+        match self {
+            Mood::Zealot => Mood::Happy,
+            Mood::Happy => Mood::Neutral,
+            Mood::Neutral => Mood::Unhappy,
+            Mood::Unhappy | Mood::Angry => Mood::Angry,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -76,7 +85,13 @@ pub struct AlivePerson {
     /// If the work status is `None`, then this person has no job.
     pub work_status: Option<WorkLethality>,
     pub building_uuid: Option<LayoutId>,
+    /// Building this person commutes to every month, see `simulation::traffic`.
+    pub workplace_uuid: Option<LayoutId>,
+    /// If the last commute went through a congested road
+    pub congested_commute: bool,
     witness_name: Option<String>,
+    /// People this entry stands for, more than one only for a cohort (see `PopulationModel`)
+    pub count: u32,
 }
 
 impl AlivePerson {
@@ -96,6 +111,7 @@ pub struct DeadPerson {
     pub cause: CauseOfDeath,
     pub building_uuid: Option<LayoutId>, // yep, the corpse can still be in a building lol
     witness_name: Option<String>,
+    pub count: u32,
 }
 
 impl DeadPerson {
@@ -114,6 +130,8 @@ pub trait BasePeopleInfo {
     fn get_dna(&self) -> DNA;
     fn get_building_uuid(&self) -> Option<&LayoutId>;
     fn is_witness(&self) -> bool;
+    /// Number of people of the entry, see `PopulationModel`
+    fn get_count(&self) -> u32;
     fn get_witness_name(&self) -> Option<&String>;
 }
 
@@ -131,8 +149,8 @@ impl BasePeopleInfo for People {
 
     fn get_dna(&self) -> DNA {
         match self {
-            People::Alive(AlivePerson { dna, .. }) => *dna,
-            People::Dead(DeadPerson { dna, .. }) => *dna,
+            People::Alive(AlivePerson { dna, .. }) => dna.clone(),
+            People::Dead(DeadPerson { dna, .. }) => dna.clone(),
         }
     }
 
@@ -156,6 +174,13 @@ impl BasePeopleInfo for People {
         }
     }
 
+    fn get_count(&self) -> u32 {
+        match self {
+            People::Alive(AlivePerson { count, .. }) => *count,
+            People::Dead(DeadPerson { count, .. }) => *count,
+        }
+    }
+
     fn get_witness_name(&self) -> Option<&String> {
         match self {
             People::Alive(AlivePerson {
@@ -227,9 +252,32 @@ impl People {
             cause: cause_of_death,
             building_uuid: self.get_building_uuid().cloned(),
             witness_name: None,
+            count: self.get_count(),
         })
     }
 
+    /// Apply `f` to `amount` people of the entry. \
+    /// The entry itself changes when it's all of them, otherwise they are split off in a new
+    /// entry which is returned and must be added to the district.
+    pub fn with_members(&mut self, amount: u32, f: impl FnOnce(&mut People)) -> Option<People> {
+        if amount == 0 {
+            return None;
+        }
+        if amount >= self.get_count() {
+            f(self);
+            return None;
+        }
+
+        let mut part = self.clone();
+        match (&mut part, &mut *self) {
+            (People::Alive(part), People::Alive(whole)) => (part.count, whole.count) = (amount, whole.count - amount),
+            (People::Dead(part), People::Dead(whole)) => (part.count, whole.count) = (amount, whole.count - amount),
+            _ => unreachable!(),
+        }
+        f(&mut part);
+        Some(part)
+    }
+
     /// A BLOODY MIRACLE! \
     /// Will not work if the person is still alive
     pub fn ressurect(&mut self) {
@@ -244,7 +292,10 @@ impl People {
             mood: Mood::Neutral,
             disease: None,
             work_status: None,
+            workplace_uuid: None,
+            congested_commute: false,
             witness_name: None,
+            count: self.get_count(),
         })
     }
 
@@ -254,6 +305,7 @@ impl People {
         mood: Mood,
         birth_place: Option<LayoutId>,
         is_witness: bool,
+        rng: &mut impl Rng,
     ) -> Self {
         People::Alive(AlivePerson {
             age_in_months: 0,
@@ -262,46 +314,60 @@ impl People {
             disease: None,
             work_status: None,
             building_uuid: birth_place,
+            workplace_uuid: None,
+            congested_commute: false,
             witness_name: if is_witness {
-                Some(BRITISH_NAMES.choose(&mut rng()).unwrap().to_string())
+                Some(BRITISH_NAMES.choose(rng).unwrap().to_string())
             } else {
                 None
             },
+            count: 1,
         })
     }
 
     /// Create a new alive people, will add a random number of DNA traits
-    pub fn create_random_people(working_age: bool, max_dna_traits: u8, is_witness: bool) -> Self {
+    pub fn create_random_people(working_age: bool, max_dna_traits: u8, is_witness: bool, rng: &mut impl Rng) -> Self {
         let age = match working_age {
-            true => 18 + random_range(0..=20),
-            false => random_range(0..18),
+            true => 18 + rng.random_range(0..=20),
+            false => rng.random_range(0..18),
         };
 
-        let mut dna_traits: u32 = 0;
+        // half of the traits are only carried by one allele, recessive ones stay hidden
+        let mut dna = DNA::new();
         for _ in 0..max_dna_traits {
-            dna_traits |= 2_u32.pow(random_range(0_u32..DNAFlags::COUNT as u32));
+            if traits().is_empty() {
+                break;
+            }
+            let id = TraitId(rng.random_range(0..traits().len()));
+            match rng.random_range(0..2) {
+                0 => dna.insert(id),
+                _ => dna.insert_allele(id),
+            }
         }
 
         People::Alive(AlivePerson {
             age_in_months: age * 12,
-            dna: DNA::from_flag(dna_traits),
+            dna,
             mood: Mood::Neutral,
             disease: None,
             work_status: None,
             building_uuid: None,
+            workplace_uuid: None,
+            congested_commute: false,
             witness_name: if is_witness {
-                Some(BRITISH_NAMES.choose(&mut rng()).unwrap().to_string())
+                Some(BRITISH_NAMES.choose(rng).unwrap().to_string())
             } else {
                 None
             },
+            count: 1,
         })
     }
 
     /// Create a new alive people, which history will be logged
-    pub fn create_witness(age: u32, dna_traits: u32, starting_building: Option<Building>) -> Self {
+    pub fn create_witness(age: u32, dna: DNA, starting_building: Option<Building>, rng: &mut impl Rng) -> Self {
         People::Alive(AlivePerson {
             age_in_months: age * 12,
-            dna: DNA::from_flag(dna_traits),
+            dna,
             mood: Mood::Neutral,
             disease: None,
             work_status: None,
@@ -310,15 +376,18 @@ impl People {
             } else {
                 None
             },
-            witness_name: Some(BRITISH_NAMES.choose(&mut rng()).unwrap().to_string()),
+            workplace_uuid: None,
+            congested_commute: false,
+            witness_name: Some(BRITISH_NAMES.choose(rng).unwrap().to_string()),
+            count: 1,
         })
     }
 
     /// Create new (alive) peoples with 1 DNA trait each.
-    pub fn create_random_population(amount: u8, number_of_witness: u8) -> Vec<People> {
+    pub fn create_random_population(amount: u8, number_of_witness: u8, rng: &mut impl Rng) -> Vec<People> {
         let mut peoples = vec![];
         for i in 0..amount {
-            peoples.push(People::create_random_people(true, 1, i < number_of_witness));
+            peoples.push(People::create_random_people(true, 1, i < number_of_witness, rng));
         }
 
         peoples
@@ -1,10 +1,17 @@
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{seq::SliceRandom, Rng};
 
-use crate::engine::layout::{Building, Layout, Road, TERMINAL_RATIO};
+use crate::engine::layout::{Layout, Road, TERMINAL_RATIO};
 
-use super::{is_area_free, AreaPartition};
+use super::{is_area_free, zoning::GrowthProfile, AreaPartition};
 
-pub fn create_building_next_to_road(road: &Road, layout: &mut Layout, rng: &mut ThreadRng) -> bool {
+/// Place a lot shaped by the zone `profile` along the road, it belongs to `district_id`.
+pub fn create_building_next_to_road(
+    road: &Road,
+    layout: &mut Layout,
+    rng: &mut impl Rng,
+    profile: &GrowthProfile,
+    district_id: usize,
+) -> bool {
     let road_area = road.get_area();
     let mut candidates = Vec::new();
 
@@ -18,18 +25,17 @@ pub fn create_building_next_to_road(road: &Road, layout: &mut Layout, rng: &mut
     candidates.shuffle(rng);
 
     for (mut bx, mut by) in candidates {
-        let width: u8 = rng.random_range(4.. 10) * TERMINAL_RATIO;
-        let height: u8 = rng.random_range(4.. 10);
+        let (width, height) = profile.random_size(rng);
 
         if rng.random_bool(0.5) {
             bx -= (width - 1) as i16
         }
         if rng.random_bool(0.5) {
-            by -= (height - TERMINAL_RATIO) as i16
+            by -= height.saturating_sub(TERMINAL_RATIO) as i16
         }
 
         if is_area_free(bx, by, width, height, layout, AreaPartition::All) {
-            let building = Building::new_at(bx, by, width, height);
+            let building = profile.create_building(bx, by, width, height, district_id, rng);
 
             layout.add_building(building);
             return true;
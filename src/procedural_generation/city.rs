@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::engine::layout::{Building, IssueSeverity, Layout, LayoutId, RoadClass, TERMINAL_RATIO};
+use crate::engine::terrain::Terrain;
+use crate::population::district::DistrictZone;
+
+use super::buildings::create_building_next_to_road;
+use super::grow_around_building;
+use super::roads::create_road_adj_to_building;
+use super::zoning::GrowthProfile;
+
+/// Growth steps without any new building before a district stops growing
+const MAX_FAILED_STEPS: usize = 50;
+/// How far from the origin to look for dry land
+const MAX_TOWN_HALL_DISTANCE: i16 = 100;
+
+/// Settings of `generate_city`, the same config always gives the same city.
+#[derive(Debug, Clone)]
+pub struct CityConfig {
+    pub seed: u64,
+    /// Number of buildings to aim for, the city stops earlier when no district has room to grow
+    pub size: usize,
+    /// Share of the buildings each zone should get, a Core district is always added
+    pub zones: Vec<(DistrictZone, u32)>,
+}
+
+impl Default for CityConfig {
+    fn default() -> Self {
+        CityConfig {
+            seed: 0,
+            size: 40,
+            zones: vec![
+                (DistrictZone::Core, 1),
+                (DistrictZone::Residentials, 3),
+                (DistrictZone::Industrials, 1),
+                (DistrictZone::Slums, 1),
+            ],
+        }
+    }
+}
+
+impl CityConfig {
+    pub fn with_seed(self, seed: u64) -> Self {
+        CityConfig { seed, ..self }
+    }
+
+    pub fn with_size(self, size: usize) -> Self {
+        CityConfig { size, ..self }
+    }
+
+    pub fn with_zones(self, zones: Vec<(DistrictZone, u32)>) -> Self {
+        CityConfig { zones, ..self }
+    }
+}
+
+/// Build a whole city from scratch, one district per zone with a weight. \
+/// Every district starts from a building next to the existing roads and grows like
+/// `generate_next_step` does, the district the furthest behind its share grows first. \
+/// Buildings that could not be linked to a road are dropped, the result is validated.
+pub fn generate_city(config: &CityConfig) -> Result<Layout, String> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut zones: Vec<(DistrictZone, u32)> = vec![(DistrictZone::Core, 0)];
+    for &(zone, weight) in config.zones.iter().filter(|(_, w)| *w > 0) {
+        match zones.iter_mut().find(|(z, _)| *z == zone) {
+            Some(existing) => existing.1 += weight,
+            None => zones.push((zone, weight)),
+        }
+    }
+    let total_weight: u32 = zones.iter().map(|(_, w)| w).sum::<u32>().max(1);
+
+    let mut layout = Layout::new(vec![], vec![]);
+    layout.districts = zones.iter().map(|(zone, _)| *zone).collect();
+    layout.terrain = Some(Terrain::new(config.seed));
+
+    // the town hall goes on the first dry land found walking away from the origin
+    let core = GrowthProfile::for_zone(DistrictZone::Core);
+    let (width, height) = core.random_size(&mut rng);
+    let Some((x, y)) = (0..MAX_TOWN_HALL_DISTANCE)
+        .flat_map(|d| (-d..=d).flat_map(move |dy| [(-d, dy), (d, dy), (dy, -d), (dy, d)]))
+        .map(|(dx, dy)| (dx * TERMINAL_RATIO as i16, dy))
+        .find(|(x, y)| layout.is_buildable(*x, *y, width, height))
+    else {
+        return Err("No dry land to build the town hall".to_string());
+    };
+    let town_hall = core.create_building(x, y, width, height, 0, &mut rng).with_name("Town Hall");
+    layout.add_building(town_hall.clone());
+    create_road_adj_to_building(&town_hall, &mut layout, &mut rng, (6, 10), RoadClass::Avenue);
+
+    let mut failed_steps = vec![0; zones.len()];
+    while layout.buildings.len() < config.size {
+        let before = layout.buildings.len();
+
+        // the district with the biggest gap between its share and its buildings
+        let Some((district_id, (zone, _))) = zones
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| failed_steps[*id] < MAX_FAILED_STEPS)
+            .max_by_key(|(id, (_, weight))| {
+                let built = layout.buildings.iter().filter(|b| b.get_district_id() == *id).count();
+                (*weight as usize * config.size) as i64 - (built * total_weight as usize) as i64
+            })
+        else {
+            // every district is stuck, the city can't get any bigger
+            break;
+        };
+        let profile = GrowthProfile::for_zone(*zone);
+
+        let seeds: Vec<&Building> =
+            layout.buildings.iter().filter(|b| b.get_district_id() == district_id).collect();
+
+        match seeds.choose(&mut rng).map(|b| (*b).clone()) {
+            Some(seed) => {
+                let demand = rng.random_range(0..=seed.get_area().len() as u32 * 3);
+                grow_around_building(&seed, &mut layout, &mut rng, &profile, district_id, demand);
+            }
+            // the district starts next to any road of the city
+            None => {
+                if let Some(road) = layout.roads.choose(&mut rng).cloned() {
+                    create_building_next_to_road(&road, &mut layout, &mut rng, &profile, district_id);
+                }
+            }
+        }
+
+        if layout.buildings.len() > before {
+            failed_steps[district_id] = 0;
+        } else {
+            failed_steps[district_id] += 1;
+        }
+    }
+
+    let isolated: Vec<LayoutId> = layout
+        .buildings
+        .iter()
+        .filter(|b| layout.graph.is_isolated(&b.id))
+        .map(|b| b.id)
+        .collect();
+    layout.buildings.retain(|b| !isolated.contains(&b.id));
+
+    drop_hidden_roads(&mut layout);
+
+    // the ids come from the thread rng, give new ones so the file only depends on the seed
+    for building in layout.buildings.iter_mut() {
+        building.id = LayoutId::random_from(&mut rng);
+    }
+    for road in layout.roads.iter_mut() {
+        road.id = LayoutId::random_from(&mut rng);
+    }
+    layout.update_graph();
+
+    let errors: Vec<String> = layout
+        .validate()
+        .into_iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .map(|i| i.to_string())
+        .collect();
+
+    if errors.is_empty() {
+        Ok(layout)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Growth stacks roads on top of each other, remove the ones entirely covered by others. \
+/// The graph is not updated, call `Layout::update_graph` after.
+fn drop_hidden_roads(layout: &mut Layout) {
+    let mut coverage: HashMap<(i16, i16), usize> = HashMap::new();
+    for road in &layout.roads {
+        for cell in road.get_area() {
+            *coverage.entry(cell).or_default() += 1;
+        }
+    }
+
+    let mut hidden = vec![];
+    for road in &layout.roads {
+        let area = road.get_area();
+        if area.iter().all(|cell| coverage[cell] > 1) {
+            for cell in area {
+                *coverage.get_mut(&cell).unwrap() -= 1;
+            }
+            hidden.push(road.id);
+        }
+    }
+
+    layout.roads.retain(|r| !hidden.contains(&r.id));
+}
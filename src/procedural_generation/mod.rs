@@ -1,36 +1,72 @@
 pub mod buildings;
+pub mod city;
 pub mod roads;
+pub mod zoning;
 
-use crate::{engine::{core::LockableEngine, drawable::Drawable, layout::Layout}, lock_read, lock_write, population::{self, Population}};
+use crate::{engine::{core::LockableEngine, drawable::Drawable, layout::{Building, Layout, LayoutId, RoadClass}}, lock_read, lock_write, population::{self, Population}};
 
 use buildings::create_building_next_to_road;
 use rand::{rngs::*, seq::*, Rng};
 use roads::*;
+use zoning::GrowthProfile;
 
+/// Grows every district according to its zone, the busier the district the more it grows. \
+/// New roads and lots belong to the district of the building that caused the growth.
 pub fn generate_next_step<'a>(engine: &LockableEngine, rng: &mut ThreadRng) {
 
     lock_write!(engine |> w);
     w.layout.roads.shuffle(rng);
     w.layout.buildings.shuffle(rng);
 
-    let graph = w.layout.graph.as_ref();
-    let population = &w.population;
-
     let binding = w.layout.buildings.clone();
-    let full_buildings: Vec<_> = binding.iter().filter(|&b| b.is_overcrowded(population) || rng.random_bool(0.2) ).collect();
-    // graph.connected_to(&building.get_building_uuid()).len() == 0
-
-    for building in full_buildings.iter() {
 
-        create_road_adj_to_building(&building, &mut w.layout, rng, (6, 10));
+    for district_id in 0..w.population.num_districts {
+        let district = w.population.get_district(district_id).unwrap();
+        let profile = GrowthProfile::for_zone(district.zone_type);
+        let chance = GrowthProfile::growth_chance(district);
 
-        let mut road = w.layout.roads.iter().choose(rng).unwrap().clone();
+        let population = &w.population;
+        let full_buildings: Vec<_> = binding
+            .iter()
+            .filter(|b| b.get_district_id() == district_id)
+            .filter(|&b| b.is_overcrowded(population) || rng.random_bool(chance))
+            .collect();
 
-        create_extension_road_toward_building(&mut road, &mut w.layout, 20, '░');
+        for building in full_buildings.iter() {
+            // the more people live in the building, the bigger the roads around it
+            let demand = building.get_num_people_in_building(&w.population) as u32;
 
-        create_road_next_to_road(&road, &mut w.layout, rng);
+            grow_around_building(building, &mut w.layout, rng, &profile, district_id, demand);
+        }
+    }
+}
 
-        create_building_next_to_road(&road, &mut w.layout, rng);
+/// Attach new roads to the building and line them with lots shaped by the zone `profile`. \
+/// `demand` is the number of people using the building, it decides the class of the roads.
+pub fn grow_around_building(
+    building: &Building,
+    layout: &mut Layout,
+    rng: &mut impl Rng,
+    profile: &GrowthProfile,
+    district_id: usize,
+    demand: u32,
+) {
+    create_road_adj_to_building(building, layout, rng, (6, 10), RoadClass::for_demand(demand));
+
+    // grow from a road next to the building so the lots stay in its district
+    let neighbors = layout.graph.connected_to(&building.id);
+    let adjacent_road = layout.roads.iter().filter(|r| neighbors.contains(&r.id)).choose(rng);
+    let mut road = match adjacent_road.or_else(|| layout.roads.iter().choose(rng)) {
+        Some(road) => road.clone(),
+        None => return,
+    };
+
+    create_extension_road_toward_building(&mut road, layout, 20, RoadClass::DirtPath);
+
+    create_road_next_to_road(&road, layout, rng, RoadClass::for_demand(demand / 2));
+
+    for _ in 0..profile.buildings_per_step {
+        create_building_next_to_road(&road, layout, rng, profile, district_id);
     }
 }
 
@@ -49,10 +85,28 @@ pub fn is_area_free(
     layout: &Layout,
     partition: AreaPartition,
 ) -> bool {
+    is_area_free_except(x, y, width, height, layout, partition, None)
+}
+
+/// Same as `is_area_free` but will not check against the building or road with the `except` id. \
+/// Used when moving or resizing something that is already in the layout.
+pub fn is_area_free_except(
+    x: i16,
+    y: i16,
+    width: u8,
+    height: u8,
+    layout: &Layout,
+    partition: AreaPartition,
+    except: Option<&LayoutId>,
+) -> bool {
+    if !layout.is_buildable(x, y, width, height) {
+        return false;
+    }
+
     let (x1, y1, x2, y2) = (x, y, x + width as i16, y + height as i16);
 
     if partition != AreaPartition::Roads {
-        for building in &layout.buildings {
+        for building in layout.buildings.iter().filter(|b| Some(&b.id) != except) {
             let bx1 = building.pos_x;
             let by1 = building.pos_y;
             let bx2 = bx1 + building.width() as i16;
@@ -65,7 +119,7 @@ pub fn is_area_free(
     }
 
     if partition != AreaPartition::Building {
-        for road in &layout.roads {
+        for road in layout.roads.iter().filter(|r| Some(&r.id) != except) {
             let rx1 = road.start_x;
             let ry1 = road.start_y;
 
@@ -1,9 +1,9 @@
 use log::debug;
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{seq::SliceRandom, Rng};
 
 use crate::engine::{
     drawable::Drawable,
-    layout::{Building, Layout, Road, ROAD_WIDTH, TERMINAL_RATIO},
+    layout::{Building, Layout, Road, RoadClass, ROAD_WIDTH, TERMINAL_RATIO},
 };
 
 use super::{is_area_free, AreaPartition};
@@ -11,8 +11,9 @@ use super::{is_area_free, AreaPartition};
 pub fn create_road_adj_to_building(
     building: &Building,
     layout: &mut Layout,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
     length_range: (u8, u8),
+    class: RoadClass,
 ) -> bool {
     let width = building.width() as i16;
     let height = building.height() as i16;
@@ -46,7 +47,7 @@ pub fn create_road_adj_to_building(
         } else {
             length
         };
-        let road_width = if horizontal { 1 } else { TERMINAL_RATIO };
+        let road_width = class.width(horizontal);
 
         if rng.random_bool(0.5) {
             if horizontal {
@@ -67,7 +68,7 @@ pub fn create_road_adj_to_building(
             layout,
             AreaPartition::All,
         ) {
-            let road = Road::new((start_x, start_y), length, road_width, horizontal, '█');
+            let road = Road::new((start_x, start_y), length, road_width, horizontal, class);
             layout.add_road(road);
             return true;
         }
@@ -76,7 +77,7 @@ pub fn create_road_adj_to_building(
     false
 }
 
-pub fn create_road_next_to_road(original: &Road, layout: &mut Layout, rng: &mut ThreadRng) -> bool {
+pub fn create_road_next_to_road(original: &Road, layout: &mut Layout, rng: &mut impl Rng, class: RoadClass) -> bool {
     let horizontal = !original.is_horizontal();
 
     let mut candidates = original.get_area();
@@ -90,8 +91,7 @@ pub fn create_road_next_to_road(original: &Road, layout: &mut Layout, rng: &mut
         } else {
             range
         };
-        let road_width = if horizontal { 1 } else { TERMINAL_RATIO };
-        let pavement = '█';
+        let road_width = class.width(horizontal);
 
         let area_width = if horizontal { length } else { road_width };
         let area_height = if horizontal { road_width } else { length };
@@ -104,7 +104,7 @@ pub fn create_road_next_to_road(original: &Road, layout: &mut Layout, rng: &mut
             layout,
             AreaPartition::Building,
         ) {
-            let road = Road::new((start_x, start_y), length, road_width, horizontal, pavement);
+            let road = Road::new((start_x, start_y), length, road_width, horizontal, class);
             layout.add_road(road);
             return true;
         }
@@ -117,7 +117,7 @@ pub fn create_extension_road_toward_building(
     road: &Road,
     layout: &mut Layout,
     max_steps: i16,
-    pavement: char,
+    class: RoadClass,
 ) {
     let (start_x, start_y) = (road.start_x, road.start_y);
     let is_horizontal = road.is_horizontal();
@@ -179,12 +179,20 @@ pub fn create_extension_road_toward_building(
                 let new_road = Road::new(
                     (new_x, new_y),
                     extension_length,
-                    if is_horizontal { 1 } else { TERMINAL_RATIO },
+                    class.width(is_horizontal),
                     is_horizontal,
-                    pavement,
+                    class,
                 );
 
-                layout.add_road(new_road);
+                // only the first lane was checked while looking for the building
+                let (area_width, area_height) = if is_horizontal {
+                    (new_road.length(), new_road.width())
+                } else {
+                    (new_road.width(), new_road.length())
+                };
+                if is_area_free(new_x, new_y, area_width, area_height, layout, AreaPartition::Building) {
+                    layout.add_road(new_road);
+                }
                 break;
             }
 
@@ -0,0 +1,116 @@
+use rand::{seq::IndexedRandom, Rng};
+
+use crate::engine::layout::{Building, BuildingFunction, TERMINAL_RATIO};
+use crate::population::district::{DistrictZone, PopulationDistrict};
+
+/// Chance for a building that is not overcrowded to grow when its district is at capacity
+const GROWTH_CHANCE: f64 = 0.2;
+
+/// How a district of a given zone grows
+pub struct GrowthProfile {
+    pub name: &'static str,
+    /// Width of the new lots in cells, it will be multiplied by `TERMINAL_RATIO`
+    pub width: (u8, u8),
+    pub height: (u8, u8),
+    pub textures: &'static [char],
+    /// New buildings placed along the road each time the district grows
+    pub buildings_per_step: u8,
+    /// Shacks are holed custom buildings instead of plain rectangles
+    pub irregular: bool,
+    pub function: BuildingFunction,
+}
+
+impl GrowthProfile {
+    pub fn for_zone(zone: DistrictZone) -> Self {
+        match zone {
+            DistrictZone::Core => GrowthProfile {
+                name: "Core Tower",
+                width: (2, 5),
+                height: (2, 5),
+                textures: &['█'],
+                buildings_per_step: 3,
+                irregular: false,
+                function: BuildingFunction::Housing,
+            },
+            DistrictZone::Residentials => GrowthProfile {
+                name: "Housing Block",
+                width: (4, 7),
+                height: (3, 6),
+                textures: &['▓'],
+                buildings_per_step: 2,
+                irregular: false,
+                function: BuildingFunction::Housing,
+            },
+            DistrictZone::Industrials => GrowthProfile {
+                name: "Factory",
+                width: (8, 13),
+                height: (6, 10),
+                textures: &['▒'],
+                buildings_per_step: 1,
+                irregular: false,
+                function: BuildingFunction::Factory,
+            },
+            DistrictZone::Slums => GrowthProfile {
+                name: "Shack",
+                width: (1, 4),
+                height: (1, 4),
+                textures: &['░', '▒'],
+                buildings_per_step: 4,
+                irregular: true,
+                function: BuildingFunction::Housing,
+            },
+        }
+    }
+
+    /// Chance for a building of the district to grow this step, higher when the district is
+    /// over its capacity.
+    pub fn growth_chance(district: &PopulationDistrict) -> f64 {
+        let pressure = district.count_alive() as f64 / district.capacity.max(1) as f64;
+        (GROWTH_CHANCE * pressure).clamp(0.0, 0.9)
+    }
+
+    /// (width, height) of a new lot, the width is already multiplied by `TERMINAL_RATIO`
+    pub fn random_size(&self, rng: &mut impl Rng) -> (u8, u8) {
+        (
+            rng.random_range(self.width.0..self.width.1) * TERMINAL_RATIO,
+            rng.random_range(self.height.0..self.height.1),
+        )
+    }
+
+    pub fn create_building(
+        &self,
+        x: i16,
+        y: i16,
+        width: u8,
+        height: u8,
+        district_id: usize,
+        rng: &mut impl Rng,
+    ) -> Building {
+        let texture = *self.textures.choose(rng).unwrap();
+        let building = Building::new_at(x, y, width, height)
+            .with_name(self.name)
+            .with_district(district_id)
+            .with_texture(texture)
+            .with_function(self.function);
+
+        if self.irregular {
+            building.with_content(Self::shack_content(width, height, texture, rng))
+        } else {
+            building
+        }
+    }
+
+    /// Ragged shape, the first cell is always built so the shack is never empty
+    fn shack_content(width: u8, height: u8, texture: char, rng: &mut impl Rng) -> Vec<String> {
+        (0..height)
+            .map(|y| {
+                (0..width / TERMINAL_RATIO)
+                    .map(|x| {
+                        let c = if (x, y) == (0, 0) || rng.random_bool(0.7) { texture } else { ' ' };
+                        c.to_string().repeat(TERMINAL_RATIO as usize)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
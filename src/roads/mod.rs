@@ -1 +1,2 @@
-pub mod road_graph;
\ No newline at end of file
+pub mod road_graph;
+pub mod routing;
@@ -1,11 +1,11 @@
 use crate::engine::drawable::Drawable;
 use crate::engine::layout::{Building, Layout, LayoutId, Road};
-use crate::utils::pair::Pair;
+use crate::roads::routing::cell_cost;
+use crate::utils::union_find::UnionFind;
 use std::collections::{HashMap, HashSet};
-use std::fmt::{Debug, Formatter};
 use log::debug;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Rect {
     pub x: i16,
     pub y: i16,
@@ -14,6 +14,15 @@ pub struct Rect {
 }
 
 impl Rect {
+    fn from_drawable(d: &dyn Drawable) -> Self {
+        Rect {
+            x: d.x(),
+            y: d.y(),
+            width: d.width(),
+            height: d.height()
+        }
+    }
+
     fn grow_by_one(&self) -> Self {
         Rect {
             x: self.x - 1,
@@ -44,118 +53,138 @@ impl Rect {
     }
 }
 
-#[derive(Copy, Clone)]
-enum Node<'a> {
-    Building(&'a Building),
-    Road(&'a Road)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Building,
+    Road
 }
 
-impl Debug for Node<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Node::Building(x) => {
-                f.debug_struct("Building").field("id", &x.id).finish()
-            }
-            Node::Road(r) => {
-                f.debug_struct("Road").field("id", &r.id).finish()
-            }
-        }
-    }
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    kind: NodeKind,
+    rect: Rect,
+    /// Time to drive along the whole road, 0 for buildings
+    cost: u32
 }
 
-impl<'a> Node<'a> {
-    pub fn id(&self) -> &'a LayoutId {
-        match self {
-            Node::Building(b) => &b.id,
-            Node::Road(r) => &r.id
+impl Node {
+    fn from_drawable(kind: NodeKind, d: &dyn Drawable, cost: u32) -> Self {
+        Node {
+            kind,
+            rect: Rect::from_drawable(d),
+            cost
         }
     }
 
-    pub fn rect(&self) -> Rect {
-        match self {
-            Node::Road(r) => {
-                Rect {
-                    x: r.x(),
-                    y: r.y(),
-                    width: r.width(),
-                    height: r.height()
-                }
-            }
-            Node::Building(b) => {
-                Rect {
-                    x: b.x(),
-                    y: b.y(),
-                    width: b.width(),
-                    height: b.height()
-                }
-            }
+    /// Buildings are only linked through roads, a road touches what is one cell around it.
+    fn touches(&self, other: &Node) -> bool {
+        match (self.kind, other.kind) {
+            (NodeKind::Building, NodeKind::Building) => false,
+            (NodeKind::Road, _) => self.rect.grow_by_one().overlap(other.rect),
+            (_, NodeKind::Road) => other.rect.grow_by_one().overlap(self.rect),
         }
     }
 }
 
-pub type Edge<'a> = Pair<'a, LayoutId>;
+/// Whether a road cell touches the drawable, the same rule links the nodes of the `Graph`.
+pub fn cell_touches(cell: (i16, i16), d: &dyn Drawable) -> bool {
+    Rect { x: cell.0, y: cell.1, width: 1, height: 1 }.grow_by_one().overlap(Rect::from_drawable(d))
+}
 
-#[derive(Debug, Clone)]
-pub struct Graph<'a> {
-    #[allow(dead_code)]
-    nodes: HashMap<LayoutId, Node<'a>>,
-    edges: HashSet<Edge<'a>>,
-    building_connections: HashSet<Pair<'a, LayoutId>>
+/// Buildings and roads of a layout with the ones they touch. \
+/// It's kept up to date by the `Layout` edit functions, call `Layout::update_graph` after moving things by hand.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: HashMap<LayoutId, Node>,
+    adjacency: HashMap<LayoutId, HashSet<LayoutId>>,
+    /// Connected parts of the network, only grows on insertion and is rebuilt on removal
+    components: UnionFind<LayoutId>
 }
 
-impl<'a> Graph<'a> {
-    pub fn get_buildings_connections(&self, id: LayoutId) -> Vec<LayoutId> {
-        let res: Vec<&LayoutId> = self.building_connections.iter().filter(|pair| pair.has(&id)).map(|pair| pair.other(&id)).collect();
-        res.into_iter().cloned().collect()
+impl Graph {
+    pub fn new(layout: &Layout) -> Graph {
+        debug!("Creating graph");
+        let mut graph = Graph::default();
+
+        for road in &layout.roads {
+            graph.insert_road(road);
+        }
+
+        for building in &layout.buildings {
+            graph.insert_building(building);
+        }
+
+        graph
     }
 
+    // ----- UPDATE -----
 
-    pub fn new(layout: &'a Layout) -> Graph<'a> {
-        debug!("Creating graph");
-        let mut nodes: HashMap<LayoutId, Node> = HashMap::new();
-        let mut edge_set: HashSet<Edge> = HashSet::new();
+    /// Also used to update a building that moved or changed size.
+    pub fn insert_building(&mut self, building: &Building) {
+        self.insert(building.id, Node::from_drawable(NodeKind::Building, building, 0))
+    }
 
+    /// Also used to update a road that moved or changed size.
+    pub fn insert_road(&mut self, road: &Road) {
+        let cost = road.length() as u32 * cell_cost(road.get_class());
+        self.insert(road.id, Node::from_drawable(NodeKind::Road, road, cost))
+    }
 
-        for layout_member in &layout.roads {
-            nodes.insert(layout_member.id.clone(), Node::Road(layout_member));
+    fn insert(&mut self, id: LayoutId, node: Node) {
+        if self.nodes.contains_key(&id) {
+            self.remove(&id);
         }
 
-        for layout_member in &layout.buildings {
-            nodes.insert(layout_member.id.clone(), Node::Building(layout_member));
-        }
+        let neighbors: HashSet<LayoutId> = self
+            .nodes
+            .iter()
+            .filter(|(_, other)| node.touches(other))
+            .map(|(other_id, _)| *other_id)
+            .collect();
 
-        for road in &layout.roads {
-            let road_node = nodes.get(&road.id).unwrap();
-            let ctangle = road_node.rect().grow_by_one();
+        for other_id in &neighbors {
+            self.adjacency.entry(*other_id).or_default().insert(id);
+            self.components.union(id, *other_id);
+        }
 
-            for other_road in &layout.roads {
-                let other_node = nodes.get(&other_road.id).unwrap();
-                if road_node.id() == other_node.id() {
-                    continue
-                }
+        self.nodes.insert(id, node);
+        self.adjacency.insert(id, neighbors);
+    }
 
-                let other_rect = other_node.rect();
+    /// Return false if the id is not in the graph.
+    pub fn remove(&mut self, id: &LayoutId) -> bool {
+        if self.nodes.remove(id).is_none() {
+            return false
+        }
 
-                if ctangle.overlap(other_rect) {
-                    edge_set.insert(Edge::new(road_node.id(), other_node.id()));
-                }
+        for other_id in self.adjacency.remove(id).unwrap_or_default() {
+            if let Some(neighbors) = self.adjacency.get_mut(&other_id) {
+                neighbors.remove(id);
             }
+        }
 
-            for other_building in &layout.buildings {
-                let other_node = nodes.get(&other_building.id).unwrap();
-                let other_rect = other_node.rect();
-
-                if ctangle.overlap(other_rect) {
-                    edge_set.insert(Edge::new(road_node.id(), other_node.id()));
-                }
+        // a union-find can't split a set
+        self.components = UnionFind::new();
+        for (id, neighbors) in &self.adjacency {
+            for other_id in neighbors {
+                self.components.union(*id, *other_id);
             }
         }
 
-        Graph {
-            nodes: nodes.clone(),
-            edges: edge_set,
-            building_connections: HashSet::new(),
-        }
+        true
+    }
+
+    // ----- QUERIES -----
+
+    /// Every other building that can be reached by road from this one.
+    pub fn get_buildings_connections(&self, id: LayoutId) -> Vec<LayoutId> {
+        self.nodes
+            .iter()
+            .filter(|(other_id, node)| {
+                node.kind == NodeKind::Building && **other_id != id && self.components.same_set(id, **other_id)
+            })
+            .map(|(other_id, _)| *other_id)
+            .collect()
     }
 
     pub fn find_path_bfs(&self, start: &LayoutId, goal: &LayoutId) -> Option<Vec<LayoutId>> {
@@ -165,16 +194,16 @@ impl<'a> Graph<'a> {
         let mut visited = HashSet::new();
         let mut came_from: HashMap<LayoutId, LayoutId> = HashMap::new();
 
-        queue.push_back(start.clone());
-        visited.insert(start.clone());
+        queue.push_back(*start);
+        visited.insert(*start);
 
         while let Some(current) = queue.pop_front() {
             if &current == goal {
-                let mut path = vec![goal.clone()];
+                let mut path = vec![*goal];
                 let mut current_id = goal;
 
                 while let Some(prev) = came_from.get(current_id) {
-                    path.push(prev.clone());
+                    path.push(*prev);
                     current_id = prev;
                 }
 
@@ -184,9 +213,9 @@ impl<'a> Graph<'a> {
 
             for neighbor in self.connected_to(&current) {
                 if !visited.contains(neighbor) {
-                    visited.insert((*neighbor).clone());
-                    came_from.insert((*neighbor).clone(), current.clone());
-                    queue.push_back((*neighbor).clone());
+                    visited.insert(*neighbor);
+                    came_from.insert(*neighbor, current);
+                    queue.push_back(*neighbor);
                 }
             }
         }
@@ -194,39 +223,214 @@ impl<'a> Graph<'a> {
         None // Aucun chemin trouve
     }
 
-    pub fn connected_to(&self, start: &LayoutId) -> HashSet<&LayoutId> {
-        self.edges.iter().filter(|x| x.has(start)).map(|x| x.other(start)).collect::<HashSet<&LayoutId>>()
-    }
+    /// Dijkstra on the whole roads (their length at their speed), only roads are used between
+    /// the two buildings. Faster than `routing::find_route` but less precise.
+    pub fn shortest_path(&self, start: &LayoutId, goal: &LayoutId) -> Option<Vec<LayoutId>> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        if !self.are_connected(start, goal) {
+            return None
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut best: HashMap<LayoutId, u32> = HashMap::new();
+        let mut came_from: HashMap<LayoutId, LayoutId> = HashMap::new();
+
+        open.push(Reverse((0, *start)));
+        best.insert(*start, 0);
 
-    pub fn start_dfs(& mut self, layout: &'a Layout) {
-        let mut connections = HashSet::new();
-        for x in layout.buildings.iter().map(|x| &x.id) {
-            let mut marks = HashSet::new();
+        while let Some(Reverse((cost, current))) = open.pop() {
+            if current == *goal {
+                let mut path = vec![*goal];
+                while let Some(prev) = came_from.get(path.last().unwrap()) {
+                    path.push(*prev);
+                }
 
-            self.recursive_dfs_to_target(&mut marks, x);
+                path.reverse();
+                return Some(path);
+            }
+
+            if cost > best[&current] || (current != *start && self.nodes[&current].kind == NodeKind::Building) {
+                continue
+            }
 
-            for y in layout.buildings.iter().map(|x| &x.id) {
-                if x != y && marks.contains(y) {
-                    connections.insert(Pair::new(x, y));
+            for neighbor in self.connected_to(&current) {
+                let next_cost = cost + self.nodes[neighbor].cost;
+                if best.get(neighbor).is_none_or(|c| next_cost < *c) {
+                    best.insert(*neighbor, next_cost);
+                    came_from.insert(*neighbor, current);
+                    open.push(Reverse((next_cost, *neighbor)));
                 }
             }
         }
 
-        self.building_connections = connections
+        None
     }
 
-    fn recursive_dfs_to_target(&'a self, mark: &mut HashSet<&'a LayoutId>, current: &'a LayoutId) {
-        mark.insert(current);
-        for x in self.connected_to(current) {
-            if mark.contains(x) {
+    /// Every building that can be reached from this one in `max_cost` or less, with the cost of
+    /// the trip. Same costs as `shortest_path`, the start is included with a cost of 0.
+    pub fn buildings_within(&self, start: &LayoutId, max_cost: u32) -> HashMap<LayoutId, u32> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        let mut reached = HashMap::new();
+        if !self.nodes.contains_key(start) {
+            return reached
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut best: HashMap<LayoutId, u32> = HashMap::new();
+
+        open.push(Reverse((0, *start)));
+        best.insert(*start, 0);
+
+        while let Some(Reverse((cost, current))) = open.pop() {
+            if cost > best[&current] {
                 continue
             }
 
-            self.recursive_dfs_to_target(mark, x)
+            if self.nodes[&current].kind == NodeKind::Building {
+                reached.insert(current, cost);
+                if current != *start {
+                    continue
+                }
+            }
+
+            for neighbor in self.connected_to(&current) {
+                let next_cost = cost + self.nodes[neighbor].cost;
+                if next_cost <= max_cost && best.get(neighbor).is_none_or(|c| next_cost < *c) {
+                    best.insert(*neighbor, next_cost);
+                    open.push(Reverse((next_cost, *neighbor)));
+                }
+            }
         }
+
+        reached
+    }
+
+    pub fn connected_to(&self, start: &LayoutId) -> HashSet<&LayoutId> {
+        self.adjacency
+            .get(start)
+            .map(|neighbors| neighbors.iter().collect())
+            .unwrap_or_default()
     }
 
     pub fn are_connected(&self, building_id_a: &LayoutId, building_id_b: &LayoutId) -> bool {
-        self.building_connections.contains(&Pair::new(building_id_a, building_id_b))
+        building_id_a != building_id_b
+            && self.nodes.contains_key(building_id_a)
+            && self.nodes.contains_key(building_id_b)
+            && self.components.same_set(*building_id_a, *building_id_b)
+    }
+}
+
+// ----- CONNECTIVITY -----
+
+/// See `Graph::connectivity_report`
+#[derive(Debug, Clone, Default)]
+pub struct ConnectivityReport {
+    /// Buildings and roads of every separate road network, biggest first
+    pub components: Vec<Vec<LayoutId>>,
+    /// Buildings next to no road
+    pub isolated_buildings: Vec<LayoutId>,
+    /// Roads that touch at most one other road and no building
+    pub dead_ends: Vec<LayoutId>,
+}
+
+impl ConnectivityReport {
+    /// One line per network, isolated building and dead end, with their names.
+    pub fn describe(&self, layout: &Layout) -> Vec<String> {
+        let name = |id: &LayoutId| match layout.buildings.iter().find(|b| b.id == *id) {
+            Some(b) => b.to_string(),
+            None => layout.roads.iter().find(|r| r.id == *id).map(|r| r.to_string()).unwrap_or_default(),
+        };
+        let is_road = |id: &LayoutId| layout.roads.iter().any(|r| r.id == *id);
+
+        let mut lines = vec![format!("{} road networks", self.components.len())];
+
+        for (i, component) in self.components.iter().enumerate() {
+            let roads = component.iter().filter(|id| is_road(id)).count();
+            lines.push(format!(
+                "network {}: {} roads, {} buildings",
+                i + 1,
+                roads,
+                component.len() - roads
+            ));
+        }
+
+        for id in &self.isolated_buildings {
+            lines.push(format!("isolated: {}", name(id)));
+        }
+
+        for id in &self.dead_ends {
+            lines.push(format!("dead end: {}", name(id)));
+        }
+
+        lines
+    }
+}
+
+impl Graph {
+    /// A building next to no road, it can't be reached.
+    pub fn is_isolated(&self, id: &LayoutId) -> bool {
+        self.nodes.get(id).is_some_and(|n| n.kind == NodeKind::Building) && self.connected_to(id).is_empty()
+    }
+
+    pub fn connectivity_report(&self) -> ConnectivityReport {
+        let mut by_root: HashMap<LayoutId, Vec<LayoutId>> = HashMap::new();
+        for id in self.nodes.keys() {
+            by_root.entry(self.components.find(*id)).or_default().push(*id);
+        }
+
+        let mut components: Vec<Vec<LayoutId>> = by_root
+            .into_values()
+            .filter(|c| c.iter().any(|id| self.nodes[id].kind == NodeKind::Road))
+            .collect();
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+        let mut isolated_buildings: Vec<LayoutId> = self.nodes.keys().filter(|id| self.is_isolated(id)).copied().collect();
+        isolated_buildings.sort();
+
+        let mut dead_ends: Vec<LayoutId> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.kind == NodeKind::Road)
+            .filter(|(id, _)| {
+                let neighbors = &self.adjacency[*id];
+                neighbors.iter().all(|n| self.nodes[n].kind == NodeKind::Road) && neighbors.len() <= 1
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        dead_ends.sort();
+
+        ConnectivityReport {
+            components,
+            isolated_buildings,
+            dead_ends,
+        }
+    }
+
+    /// Number of buildings that could reach each other in `before` but are now cut from
+    /// the biggest part of their old network. Buildings removed since are not counted.
+    pub fn cut_off_buildings(&self, before: &Graph) -> usize {
+        let mut old_networks: HashMap<LayoutId, HashMap<LayoutId, usize>> = HashMap::new();
+
+        for (id, node) in &before.nodes {
+            if node.kind == NodeKind::Building && self.nodes.contains_key(id) && !before.is_isolated(id) {
+                *old_networks
+                    .entry(before.components.find(*id))
+                    .or_default()
+                    .entry(self.components.find(*id))
+                    .or_default() += 1;
+            }
+        }
+
+        old_networks
+            .values()
+            .map(|parts| parts.values().sum::<usize>() - parts.values().max().unwrap_or(&0))
+            .sum()
     }
 }
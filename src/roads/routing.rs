@@ -0,0 +1,112 @@
+use crate::engine::drawable::Drawable;
+use crate::engine::layout::{Layout, LayoutId, RoadClass};
+use crate::roads::road_graph::cell_touches;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Meters in one cell of the map
+pub const CELL_LENGTH: f32 = 10.0;
+
+/// Cost of driving through one cell of this class of road, in tenths of a second.
+pub fn cell_cost(class: RoadClass) -> u32 {
+    let meters_per_second = class.speed() as f32 / 3.6;
+    ((CELL_LENGTH / meters_per_second * 10.0).round() as u32).max(1)
+}
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// Road cells from the start building to the goal building
+    pub cells: Vec<(i16, i16)>,
+    pub cost: u32,
+}
+
+impl Route {
+    /// In meters, the route can jump the cells between two roads that touch.
+    pub fn length(&self) -> f32 {
+        let jumps: u32 = self.cells.windows(2).map(|w| manhattan(w[0], w[1]) - 1).sum();
+        (self.cells.len() as u32 + jumps) as f32 * CELL_LENGTH
+    }
+
+    /// In seconds
+    pub fn travel_time(&self) -> f32 {
+        self.cost as f32 / 10.0
+    }
+}
+
+fn manhattan(a: (i16, i16), b: (i16, i16)) -> u32 {
+    ((a.0 - b.0).abs() + (a.1 - b.1).abs()) as u32
+}
+
+/// Distance from the cell to the closest cell of the rectangle.
+fn manhattan_to(cell: (i16, i16), d: &dyn Drawable) -> u32 {
+    let dx = (d.x() - cell.0).max(cell.0 - (d.x() + d.width() as i16 - 1)).max(0);
+    let dy = (d.y() - cell.1).max(cell.1 - (d.y() + d.height() as i16 - 1)).max(0);
+    (dx + dy) as u32
+}
+
+/// A* over the road cells between two buildings, None if they are not connected. \
+/// Cells are linked like the nodes of the road graph (see `cell_touches`), a step costs the
+/// distance driven at the speed of the next cell. The heuristic is the Manhattan distance to the
+/// goal at highway speed.
+pub fn find_route(layout: &Layout, start: &LayoutId, goal: &LayoutId) -> Option<Route> {
+    if !layout.graph.are_connected(start, goal) {
+        return None;
+    }
+
+    let start_bldg = layout.buildings.iter().find(|b| b.id == *start)?;
+    let goal_bldg = layout.buildings.iter().find(|b| b.id == *goal)?;
+
+    // fastest road wins where roads overlap
+    let mut costs: HashMap<(i16, i16), u32> = HashMap::new();
+    for road in &layout.roads {
+        let cost = cell_cost(road.class);
+        for cell in road.get_area() {
+            costs.entry(cell).and_modify(|c| *c = (*c).min(cost)).or_insert(cost);
+        }
+    }
+
+    let best_cost = cell_cost(RoadClass::Highway);
+    // the last cell can be up to 2 cells away from the goal on both axes
+    let heuristic = |cell: (i16, i16)| manhattan_to(cell, goal_bldg).saturating_sub(4) * best_cost;
+
+    let mut open = BinaryHeap::new();
+    let mut best: HashMap<(i16, i16), u32> = HashMap::new();
+    let mut came_from: HashMap<(i16, i16), (i16, i16)> = HashMap::new();
+
+    for (&cell, &cost) in costs.iter().filter(|(cell, _)| cell_touches(**cell, start_bldg)) {
+        best.insert(cell, cost);
+        open.push(Reverse((cost + heuristic(cell), cost, cell)));
+    }
+
+    while let Some(Reverse((_, cost, cell))) = open.pop() {
+        if cost > best[&cell] {
+            continue;
+        }
+
+        if cell_touches(cell, goal_bldg) {
+            let mut cells = vec![cell];
+            while let Some(prev) = came_from.get(cells.last().unwrap()) {
+                cells.push(*prev);
+            }
+            cells.reverse();
+
+            return Some(Route { cells, cost });
+        }
+
+        let around = (-2..=2).flat_map(|dx| (-2..=2).map(move |dy| (cell.0 + dx, cell.1 + dy)));
+        for next in around.filter(|&next| next != cell) {
+            let Some(step) = costs.get(&next) else {
+                continue;
+            };
+
+            let next_cost = cost + step * manhattan(cell, next);
+            if best.get(&next).is_none_or(|c| next_cost < *c) {
+                best.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,225 @@
+use crate::population::disease::DiseaseLethality;
+use crate::population::district::DistrictZone;
+use crate::population::people::{Mood, WorkLethality};
+use serde::{Deserialize, Serialize};
+
+/// Coefficients of the simulation, loaded from a JSON file given with `--balance`. \
+/// Missing sections and fields keep their default value, `--default-balance` prints the whole file.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Balance {
+    pub births: BirthBalance,
+    pub deaths: DeathBalance,
+    pub districts: DistrictBalance,
+}
+
+impl Balance {
+    pub fn from_json(json: &str) -> Result<Balance, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// One value for each zone, the four of them must be given
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerZone<T> {
+    pub core: T,
+    pub residentials: T,
+    pub industrials: T,
+    pub slums: T,
+}
+
+impl<T: Copy> PerZone<T> {
+    pub fn get(&self, zone: DistrictZone) -> T {
+        match zone {
+            DistrictZone::Core => self.core,
+            DistrictZone::Residentials => self.residentials,
+            DistrictZone::Industrials => self.industrials,
+            DistrictZone::Slums => self.slums,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerMood {
+    pub zealot: f64,
+    pub happy: f64,
+    pub neutral: f64,
+    pub unhappy: f64,
+    pub angry: f64,
+}
+
+impl PerMood {
+    pub fn get(&self, mood: Mood) -> f64 {
+        match mood {
+            Mood::Zealot => self.zealot,
+            Mood::Happy => self.happy,
+            Mood::Neutral => self.neutral,
+            Mood::Unhappy => self.unhappy,
+            Mood::Angry => self.angry,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerLethality {
+    pub low: f64,
+    pub moderate: f64,
+    pub deadly: f64,
+}
+
+impl PerLethality {
+    pub fn get(&self, lethality: DiseaseLethality) -> f64 {
+        match lethality {
+            DiseaseLethality::Low => self.low,
+            DiseaseLethality::Moderate => self.moderate,
+            DiseaseLethality::Deadly => self.deadly,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerWorkRisk {
+    pub safe_job: f64,
+    pub medium_risks: f64,
+    pub high_risks: f64,
+    pub extreme_risks: f64,
+}
+
+impl PerWorkRisk {
+    pub fn get(&self, risk: WorkLethality) -> f64 {
+        match risk {
+            WorkLethality::SafeJob => self.safe_job,
+            WorkLethality::MediumRisks => self.medium_risks,
+            WorkLethality::HighRisks => self.high_risks,
+            WorkLethality::ExtremeRisks => self.extreme_risks,
+        }
+    }
+}
+
+/// Bell curve of the age, 1.0 at `centre` years
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AgeCurve {
+    pub centre: f64,
+    /// Years from the centre where the curve falls to 1/e
+    pub width: f64,
+}
+
+impl AgeCurve {
+    /// `shift` moves the centre, in years
+    pub fn at(&self, age: u32, shift: f64) -> f64 {
+        f64::exp(-((age as f64 - (self.centre + shift)) / self.width).powf(2.0))
+    }
+}
+
+/// See `births::birth_probability`, every bonus multiplies the chance to have a child
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BirthBalance {
+    /// Chance for each trait to mutate, the `mutation` effects of the parents are added
+    pub mutation_percentage: f64,
+    /// Families have more children when the schools around have room for them
+    pub school_fertility_bonus: f64,
+    pub fertility_age: AgeCurve,
+    /// Share of the bonus depending on the district happiness, the rest is always given
+    pub happiness_weight: f64,
+    pub zone_bonus: PerZone<f64>,
+    pub mood_bonus: PerMood,
+    pub sickness_bonus: PerLethality,
+    pub working_bonus: f64,
+    pub unemployed_bonus: f64,
+    /// Multiplies the working or unemployed bonus
+    pub work_scale: f64,
+    /// Removed once the whole district works
+    pub employment_penalty: f64,
+}
+
+impl Default for BirthBalance {
+    fn default() -> Self {
+        BirthBalance {
+            mutation_percentage: 0.07,
+            school_fertility_bonus: 0.2,
+            fertility_age: AgeCurve { centre: 30.0, width: 10.0 },
+            happiness_weight: 0.5,
+            zone_bonus: PerZone { core: 0.7, residentials: 1.1, industrials: 0.85, slums: 1.2 },
+            mood_bonus: PerMood { zealot: 1.15, happy: 1.1, neutral: 1.0, unhappy: 0.8, angry: 0.7 },
+            sickness_bonus: PerLethality { low: 0.9, moderate: 0.6, deadly: 0.1 },
+            working_bonus: 0.9,
+            unemployed_bonus: 1.1,
+            work_scale: 1.1,
+            employment_penalty: 0.2,
+        }
+    }
+}
+
+/// See `deaths::death_odds`, every bonus multiplies the chance to die
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeathBalance {
+    /// Share of the extra sickness deaths avoided when the hospitals around have room for everyone
+    pub healthcare_effect: f64,
+    /// Share of the extra work accidents avoided when the schools around have room for every child
+    pub education_effect: f64,
+    /// Share of the extra murders avoided when the police can watch over everyone
+    pub policing_effect: f64,
+    /// Moved by the `lifespan` effects of the traits
+    pub age: AgeCurve,
+    /// (minimum district happiness, bonus), the first one reached is used
+    pub happiness_bonus: Vec<(f64, f64)>,
+    /// Bonus when the district is under every happiness step
+    pub unhappiest_bonus: f64,
+    pub zone_bonus: PerZone<f64>,
+    /// Added for a home fully built on irradiated ground
+    pub radiation_bonus: f64,
+    /// Added when the district grows no food at all
+    pub hunger_bonus: f64,
+    pub mood_bonus: PerMood,
+    pub sickness_bonus: PerLethality,
+    pub work_bonus: PerWorkRisk,
+    /// Multiplies the work bonus of workers stuck in traffic jams
+    pub congested_commute_bonus: f64,
+    pub homeless_bonus: f64,
+}
+
+impl Default for DeathBalance {
+    fn default() -> Self {
+        DeathBalance {
+            healthcare_effect: 0.6,
+            education_effect: 0.4,
+            policing_effect: 0.5,
+            age: AgeCurve { centre: 60.0, width: 15.0 },
+            happiness_bonus: vec![(0.9, 0.9), (0.7, 1.0), (0.5, 1.1), (0.3, 1.2)],
+            unhappiest_bonus: 1.4,
+            zone_bonus: PerZone { core: 0.9, residentials: 1.0, industrials: 1.2, slums: 1.5 },
+            radiation_bonus: 0.8,
+            hunger_bonus: 0.6,
+            mood_bonus: PerMood { zealot: 0.8, happy: 0.9, neutral: 1.0, unhappy: 1.1, angry: 1.2 },
+            sickness_bonus: PerLethality { low: 1.1, moderate: 1.5, deadly: 1.8 },
+            work_bonus: PerWorkRisk { safe_job: 1.0, medium_risks: 1.2, high_risks: 1.4, extreme_risks: 1.65 },
+            congested_commute_bonus: 1.15,
+            homeless_bonus: 1.45,
+        }
+    }
+}
+
+/// See `Population::add_district`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistrictBalance {
+    pub capacity: PerZone<u16>,
+    /// Maximum number of districts each one can be linked to
+    pub max_links: PerZone<usize>,
+    /// People in each district when the game starts
+    pub starting_population: u8,
+    pub starting_witnesses: u8,
+}
+
+impl Default for DistrictBalance {
+    fn default() -> Self {
+        DistrictBalance {
+            capacity: PerZone { core: 50, residentials: 100, industrials: 75, slums: 150 },
+            max_links: PerZone { core: 4, residentials: 3, industrials: 2, slums: 1 },
+            starting_population: 100,
+            starting_witnesses: 2,
+        }
+    }
+}
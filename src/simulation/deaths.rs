@@ -1,27 +1,66 @@
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 use crate::engine::layout::LayoutId;
+use crate::simulation::services::ServiceLevels;
 use crate::population::{
-    disease::{Disease, DiseaseLethality},
+    disease::Disease,
     district::DistrictZone,
-    dna::{DNAFlags, DNA},
-    people::{AlivePerson, CauseOfDeath, Mood, WorkLethality},
+    dna::DNA,
+    people::{AlivePerson, CauseOfDeath, WorkLethality},
 };
 
-/// Return a cause of dead if the person should *die*.
-pub fn check_death(
+use super::balance::DeathBalance;
+
+/// Chance to die this month and the weight of each cause
+pub struct DeathOdds {
+    pub probability: f64,
+    causes: [(CauseOfDeath, f64); 6],
+}
+
+impl DeathOdds {
+    /// Cause of a death, picked by weight
+    pub fn pick_cause(&self, rng: &mut impl Rng) -> CauseOfDeath {
+        let total_weight: f64 = self.causes.iter().map(|(_, w)| w).sum();
+        let mut roll = rng.random::<f64>() * total_weight;
+
+        for (cause, weight) in self.causes {
+            if roll < weight {
+                return cause;
+            }
+            roll -= weight;
+        }
+
+        // Fallback (shouldn't happen)
+        CauseOfDeath::OldAge
+    }
+}
+
+/// Odds of the person to *die*, the same for every member of a cohort.
+pub fn death_odds(
     people: &AlivePerson,
     district_zone: DistrictZone,
     district_happiness: f64,
-    rng: &mut ThreadRng
-) -> Option<(CauseOfDeath, f64)> {
-    let work_bonus = work_bonus(&people.work_status);
-    let sickness_bonus = sickness_bonus(&people.disease);
-    let zone_bonus = zone_bonus(district_zone);
-    let homelesness_bonus = homeless_bonus(&people.building_uuid);
-    let deathrate_from_age = deathrate_from_age(people.get_age(), people.dna);
-    let dna_bonus = dna_bonus(people.dna);
-    let mood_bonus = mood_bonus(&people.mood);
-    let happiness_bonus = happiness_bonus(district_happiness);
+    food_supply: f64,
+    radiation_exposure: f64,
+    services: ServiceLevels,
+    balance: &DeathBalance,
+) -> DeathOdds {
+    let work_bonus = with_service(
+        work_bonus(&people.work_status, people.congested_commute, balance),
+        services.education,
+        balance.education_effect,
+    );
+    let sickness_bonus = with_service(
+        sickness_bonus(&people.disease, &people.dna, balance),
+        services.healthcare,
+        balance.healthcare_effect,
+    );
+    let zone_bonus = balance.zone_bonus.get(district_zone) * radiation_bonus(radiation_exposure, balance);
+    let hunger_bonus = hunger_bonus(food_supply, balance);
+    let homelesness_bonus = homeless_bonus(&people.building_uuid, balance);
+    let deathrate_from_age = deathrate_from_age(people.get_age(), &people.dna, balance);
+    let dna_bonus = people.dna.effects().frailty;
+    let mood_bonus = with_service(balance.mood_bonus.get(people.mood), services.policing, balance.policing_effect);
+    let happiness_bonus = happiness_bonus(district_happiness, balance);
 
     let death_probability = deathrate_from_age
         * dna_bonus
@@ -30,117 +69,82 @@ pub fn check_death(
         * zone_bonus
         * sickness_bonus
         * work_bonus
-        * homelesness_bonus;
+        * homelesness_bonus
+        * hunger_bonus;
 
-    let dice: f64 = rng.random();
-
-    if dice < death_probability {
-        let causes = vec![
+    DeathOdds {
+        probability: death_probability,
+        causes: [
             (CauseOfDeath::OldAge, deathrate_from_age * dna_bonus),
             (CauseOfDeath::Sickness, sickness_bonus),
             (CauseOfDeath::Radiations, zone_bonus),
             (CauseOfDeath::WorkAccident, work_bonus),
-            (CauseOfDeath::Poverty, homelesness_bonus),
+            (CauseOfDeath::Poverty, homelesness_bonus * hunger_bonus),
             (CauseOfDeath::Murder, 1.0 - mood_bonus),
-            (CauseOfDeath::EatenByMonster, 1.0 - happiness_bonus),
-        ];
-
-        let total_weight: f64 = causes.iter().map(|(_, w)| w).sum();
-        let mut roll = rng.random::<f64>() * total_weight;
-
-        for (cause, weight) in causes {
-            if roll < weight {
-                return Some((cause, weight));
-            }
-            roll -= weight;
-        }
-
-        // Fallback (shouldn't happen)
-        Some((CauseOfDeath::OldAge, 1.0))
-    } else {
-        None
+        ],
     }
 }
 
-/// Simple bell curve centred arround 70 years
-fn deathrate_from_age(age: u32, dna: DNA) -> f64 {
-    f64::exp(
-        -((age as f64
-            - (60.0
-                - if dna.contains(DNAFlags::ShortLifespan) {
-                    25.0
-                } else {
-                    0.0
-                }))
-            / 15.0)
-            .powf(2.0),
-    )
+/// Bell curve of `DeathBalance::age`, moved by the `lifespan` effects of the traits
+fn deathrate_from_age(age: u32, dna: &DNA, balance: &DeathBalance) -> f64 {
+    balance.age.at(age, dna.effects().lifespan)
 }
 
-fn happiness_bonus(percentage: f64) -> f64 {
-    match percentage {
-        p if p >= 0.9 => 0.9,
-        p if p >= 0.7 => 1.0,
-        p if p >= 0.5 => 1.1,
-        p if p >= 0.3 => 1.2,
-        _ => 1.4,
-    }
+fn happiness_bonus(percentage: f64, balance: &DeathBalance) -> f64 {
+    balance
+        .happiness_bonus
+        .iter()
+        .find(|(min, _)| percentage >= *min)
+        .map_or(balance.unhappiest_bonus, |(_, bonus)| *bonus)
 }
 
-fn zone_bonus(district_type: DistrictZone) -> f64 {
-    match district_type {
-        DistrictZone::Slums => 1.5,
-        DistrictZone::Industrials => 1.2,
-        DistrictZone::Residentials => 1.0,
-        DistrictZone::Core => 0.9,
-    }
+/// `exposure` is the share of the home built on irradiated ground
+fn radiation_bonus(exposure: f64, balance: &DeathBalance) -> f64 {
+    1.0 + balance.radiation_bonus * exposure.clamp(0.0, 1.0)
 }
 
-fn mood_bonus(mood: &Mood) -> f64 {
-    match mood {
-        Mood::Zealot => 0.8,
-        Mood::Happy => 0.9,
-        Mood::Neutral => 1.0,
-        Mood::Unhappy => 1.1,
-        Mood::Angry => 1.2,
+/// No bonus while the district grows enough food
+fn hunger_bonus(food_supply: f64, balance: &DeathBalance) -> f64 {
+    1.0 + balance.hunger_bonus * (1.0 - food_supply).clamp(0.0, 1.0)
+}
+
+/// Services only lower the part of the bonus above 1.0, `service` is the coverage of the home
+/// (see `Services::get_levels`).
+fn with_service(bonus: f64, service: f32, effect: f64) -> f64 {
+    if bonus > 1.0 {
+        1.0 + (bonus - 1.0) * (1.0 - effect * service.clamp(0.0, 1.0) as f64)
+    } else {
+        bonus
     }
 }
 
-fn sickness_bonus(disease: &Option<Disease>) -> f64 {
+/// The `sickness` effects of the traits only change the part of the bonus above 1.0
+fn sickness_bonus(disease: &Option<Disease>, dna: &DNA, balance: &DeathBalance) -> f64 {
     if let Some(disease) = disease {
-        match disease.lethality {
-            DiseaseLethality::Low => 1.1,
-            DiseaseLethality::Moderate => 1.5,
-            DiseaseLethality::Deadly => 1.8,
-        }
+        1.0 + (balance.sickness_bonus.get(disease.lethality) - 1.0) * dna.effects().sickness
     } else {
         1.0
     }
 }
 
-fn work_bonus(work_lethality: &Option<WorkLethality>) -> f64 {
+/// Workers stuck in traffic jams arrive tired and have more accidents.
+fn work_bonus(work_lethality: &Option<WorkLethality>, congested_commute: bool, balance: &DeathBalance) -> f64 {
     if let Some(work) = work_lethality {
-        match *work {
-            WorkLethality::SafeJob => 1.0,
-            WorkLethality::MediumRisks => 1.2,
-            WorkLethality::HighRisks => 1.4,
-            WorkLethality::ExtremeRisks => 1.65,
+        let risk = balance.work_bonus.get(*work);
+
+        if congested_commute {
+            risk * balance.congested_commute_bonus
+        } else {
+            risk
         }
     } else {
         1.0
     }
 }
 
-fn dna_bonus(dna: DNA) -> f64 {
-    match dna.contains(DNAFlags::FragilePhysique) {
-        true => 1.3,
-        false => 1.0,
-    }
-}
-
-fn homeless_bonus(building_uuid: &Option<LayoutId>) -> f64 {
+fn homeless_bonus(building_uuid: &Option<LayoutId>, balance: &DeathBalance) -> f64 {
     match building_uuid {
         Some(_building) => 1.0,
-        None => 1.45,
+        None => balance.homeless_bonus,
     }
 }
@@ -0,0 +1,91 @@
+use crate::engine::core::LockableEngine;
+use crate::engine::layout::LayoutId;
+use crate::population::disease::{diseases, Disease, DiseaseId, DiseaseLethality};
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+use rand::rngs::ThreadRng;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Will forward one month of diseases \
+/// The sick get better once their disease has run its course, meanwhile they give it to the
+/// people living in the same building (see `DiseaseContagionRate::chance`). Each disease can
+/// also break out in a district on its own. Only the ages affected by a disease can catch it,
+/// the `infection` effect of the traits changes the chances.
+pub fn update_diseases(engine: &LockableEngine, rng: &mut ThreadRng) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    let mut messages = vec![];
+    for district_id in 0..e.population.num_districts {
+        let district = e.population.get_district_mut(district_id).unwrap();
+
+        let mut sick: HashMap<(LayoutId, DiseaseId), i32> = HashMap::new();
+        for person in district.peoples.iter_mut().filter_map(|p| p.as_alive_mut()) {
+            let Some(disease) = &mut person.disease else {
+                continue;
+            };
+            disease.months_left = disease.months_left.saturating_sub(1);
+            if disease.months_left == 0 {
+                person.disease = None;
+            } else if let Some(home) = person.building_uuid {
+                *sick.entry((home, disease.kind)).or_default() += person.count as i32;
+            }
+        }
+
+        // each kind in turn, the people who caught one can't catch the next ones
+        for kind in diseases().ids() {
+            let def = diseases().get(kind);
+            district.affect_alive(
+                rng,
+                |person| {
+                    let Some(count) = person.building_uuid.and_then(|home| sick.get(&(home, kind))) else {
+                        return 0.0;
+                    };
+                    if person.disease.is_some() || !def.affects(person.get_age()) {
+                        return 0.0;
+                    }
+                    (1.0 - (1.0 - def.contagion.chance()).powi(*count)) * person.dna.effects().infection
+                },
+                |p| p.as_alive_mut().unwrap().disease = Some(Disease::catch(kind)),
+            );
+        }
+
+        for kind in diseases().ids() {
+            let def = diseases().get(kind);
+            if !rng.random_bool(def.outbreak) {
+                continue;
+            }
+
+            // index of each healthy entry weighted by its members
+            let healthy: Vec<(usize, u32)> = district
+                .peoples
+                .iter()
+                .enumerate()
+                .filter_map(|(index, p)| p.as_alive().map(|alive| (index, alive)))
+                .filter(|(_, p)| p.disease.is_none() && def.affects(p.get_age()))
+                .map(|(index, p)| (index, p.count))
+                .collect();
+            if let Ok((index, _)) = healthy.choose_weighted(rng, |(_, count)| *count) {
+                let part = district.peoples[*index]
+                    .with_members(1, |p| p.as_alive_mut().unwrap().disease = Some(Disease::catch(kind)));
+                district.peoples.extend(part);
+                let color = match def.lethality {
+                    DiseaseLethality::Deadly => LogColor::Important,
+                    _ => LogColor::Unusual,
+                };
+                messages.push((format!("{} broke out in the {} district", def.name, district.zone_type), color));
+            }
+        }
+
+        district.recalcul_sickness();
+    }
+
+    for (message, color) in messages {
+        send_to_side_bar_auto!(w, &w, message, LogType::Event, color);
+    }
+
+    lock_unlock!(w);
+}
@@ -1,30 +1,19 @@
-use crate::population::dna::DNA;
+use crate::population::dna::{traits, DNA};
+use rand::Rng;
 
-pub fn mix_dna(parent1: DNA, parent2: DNA) -> DNA {
-    let mut child_dna = DNA::new();
-    for i in 0..32 {
-        let mask = 1 << i;
-        let bit1 = parent1 & DNA::from_flag(mask);
-        let bit2 = parent2 & DNA::from_flag(mask);
-
-        if bit1 == bit2 {
-            child_dna |= bit1;
-        } else {
-            if rand::random::<f64>() < 0.7 {
-                child_dna |= bit1.max(bit2);
-            } else {
-                child_dna |= bit1.min(bit2);
-            }
-        }
-    }
-    child_dna
+/// The child gets one allele of each trait from each parent, picked at random on their side.
+pub fn mix_dna(parent1: &DNA, parent2: &DNA, rng: &mut impl Rng) -> DNA {
+    DNA::from_gametes(parent1.gamete(rng), parent2.gamete(rng))
 }
 
-pub fn mutate_dna(dna: DNA, mutation_rate: f64) -> DNA {
+/// Both alleles of every trait can flip, with `mutation_rate` times the `mutation_rate` of the trait.
+pub fn mutate_dna(dna: DNA, mutation_rate: f64, rng: &mut impl Rng) -> DNA {
     let mut mutated_dna = dna;
-    for i in 0..32 {
-        if rand::random::<f64>() < mutation_rate {
-            mutated_dna ^= DNA::from_flag(1 << i);
+    for id in traits().ids() {
+        for allele in 0..2 {
+            if rng.random::<f64>() < mutation_rate * traits().get(id).mutation_rate {
+                mutated_dna.toggle_allele(id, allele);
+            }
         }
     }
     mutated_dna
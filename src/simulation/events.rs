@@ -0,0 +1,319 @@
+use crate::engine::core::{Engine, LockableEngine};
+use crate::engine::layout::{BuildingType, LayoutId};
+use crate::population::people::{BasePeopleInfo, CauseOfDeath, Mood, People, PeopleLegalState};
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+use super::births::spawn_childs;
+use super::make_pairs;
+use crate::population::cohort::binomial;
+use rand::rngs::ThreadRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Chance for each person in a burning building to die
+const FIRE_DEATH_CHANCE: f64 = 0.4;
+/// Happiness under which a district can riot
+const RIOT_HAPPINESS: f32 = 0.25;
+/// Share of the district killed during a riot
+const RIOT_DEATHS: f64 = 0.02;
+/// Damages paid by the city for each angry person
+const RIOT_DAMAGES: u32 = 5;
+const FESTIVAL_COST: u32 = 300;
+/// Chance for each person to cheer up during a festival
+const FESTIVAL_MOOD_CHANCE: f64 = 0.5;
+/// Months between a festival and the baby boom it causes
+const FESTIVAL_BABY_BOOM_DELAY: u32 = 9;
+/// Share of the adults having a baby during a baby boom
+const BABY_BOOM_BIRTHS: f64 = 0.05;
+/// Money earned by the city for each worker of the district
+const BOOM_BONUS: u32 = 20;
+
+/// When an event can fire, its precondition must hold too
+#[derive(Debug, Clone, Copy)]
+pub enum Trigger {
+    /// Chance each month for each district
+    Random(f64),
+    /// Once every this many months
+    Every(u32),
+}
+
+/// Something happening to a whole district, see `EVENTS`
+pub struct CityEvent {
+    pub name: &'static str,
+    pub trigger: Trigger,
+    /// Months before the event can fire again in the same district
+    pub cooldown: u32,
+    pub precondition: fn(&Engine, usize) -> bool,
+    /// Change the city and return the messages for the sidebar
+    pub effect: fn(&mut Engine, usize, &mut ThreadRng) -> Vec<(String, LogColor)>,
+}
+
+pub const EVENTS: [CityEvent; 5] = [
+    CityEvent {
+        name: "Fire",
+        trigger: Trigger::Random(0.002),
+        cooldown: 24,
+        precondition: has_buildings,
+        effect: fire,
+    },
+    CityEvent {
+        name: "Riot",
+        trigger: Trigger::Random(0.05),
+        cooldown: 12,
+        precondition: is_unhappy,
+        effect: riot,
+    },
+    CityEvent {
+        name: "Festival",
+        trigger: Trigger::Every(12),
+        cooldown: 12,
+        precondition: can_celebrate,
+        effect: festival,
+    },
+    CityEvent {
+        name: "Baby boom",
+        trigger: Trigger::Random(0.001),
+        cooldown: 24,
+        precondition: is_thriving,
+        effect: baby_boom,
+    },
+    CityEvent {
+        name: "Economic boom",
+        trigger: Trigger::Random(0.002),
+        cooldown: 36,
+        precondition: has_workers,
+        effect: economic_boom,
+    },
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Events {
+    month: u32,
+    /// Month each event was last fired in each district
+    last_fired: HashMap<(&'static str, usize), u32>,
+    /// (month, event, district) of the events fired whatever their trigger and cooldown
+    scheduled: Vec<(u32, &'static str, usize)>,
+}
+
+impl Events {
+    /// Fire the event in the district in `months` months, if its precondition holds then.
+    pub fn schedule(&mut self, name: &'static str, district_id: usize, months: u32) {
+        self.scheduled.push((self.month + months, name, district_id));
+    }
+}
+
+/// Will forward one month of city events \
+/// Every event of `EVENTS` whose trigger, cooldown and precondition allow it is fired in each
+/// district, as well as the ones scheduled for this month (see `Events::schedule`).
+pub fn update_events(engine: &LockableEngine, rng: &mut ThreadRng) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    e.events.month += 1;
+    let month = e.events.month;
+    let (due, later): (Vec<_>, Vec<_>) = e.events.scheduled.drain(..).partition(|(m, _, _)| *m <= month);
+    e.events.scheduled = later;
+
+    let mut messages = vec![];
+    for district_id in 0..e.population.num_districts {
+        for event in EVENTS.iter() {
+            let scheduled = due.iter().any(|(_, name, d)| *name == event.name && *d == district_id);
+            let cooling_down = e
+                .events
+                .last_fired
+                .get(&(event.name, district_id))
+                .is_some_and(|last| month - last < event.cooldown);
+
+            let fires = scheduled
+                || !cooling_down
+                    && match event.trigger {
+                        Trigger::Random(chance) => rng.random_bool(chance),
+                        Trigger::Every(months) => month.is_multiple_of(months),
+                    };
+            if !fires || !(event.precondition)(e, district_id) {
+                continue;
+            }
+
+            e.events.last_fired.insert((event.name, district_id), month);
+            messages.extend((event.effect)(e, district_id, rng));
+        }
+    }
+
+    if messages.is_empty() {
+        lock_unlock!(w);
+        return;
+    }
+
+    for district_id in 0..e.population.num_districts {
+        e.population.get_district_mut(district_id).unwrap().recalcul_happiness();
+    }
+    e.refresh();
+
+    for (message, color) in messages {
+        send_to_side_bar_auto!(w, &w, message, LogType::Event, color);
+    }
+
+    lock_unlock!(w);
+}
+
+// ----- PRECONDITIONS -----
+
+fn has_buildings(e: &Engine, district_id: usize) -> bool {
+    e.layout
+        .buildings
+        .iter()
+        .any(|b| b.get_district_id() == district_id && b.get_building_type() != BuildingType::EmptySpace)
+}
+
+fn is_unhappy(e: &Engine, district_id: usize) -> bool {
+    let district = e.population.get_district(district_id).unwrap();
+    district.num_people > 0 && district.get_happiness_percentage() < RIOT_HAPPINESS
+}
+
+fn can_celebrate(e: &Engine, district_id: usize) -> bool {
+    let district = e.population.get_district(district_id).unwrap();
+    district.num_people > 0
+        && district.get_happiness_percentage() >= RIOT_HAPPINESS
+        && e.treasury.get_balance() >= FESTIVAL_COST as i64
+}
+
+fn is_thriving(e: &Engine, district_id: usize) -> bool {
+    let district = e.population.get_district(district_id).unwrap();
+    district.food_supply >= 1.0 && district.get_happiness_percentage() >= 0.5
+}
+
+fn has_workers(e: &Engine, district_id: usize) -> bool {
+    !e.treasury.is_bankrupt() && count_workers(e, district_id) > 0
+}
+
+fn count_workers(e: &Engine, district_id: usize) -> usize {
+    e.population
+        .get_district(district_id)
+        .unwrap()
+        .peoples
+        .iter()
+        .filter_map(|p| p.as_alive())
+        .filter(|p| p.workplace_uuid.is_some())
+        .map(|p| p.count as usize)
+        .sum()
+}
+
+// ----- EFFECTS -----
+
+/// A building burns down, some of the people inside die and it becomes an empty lot.
+fn fire(e: &mut Engine, district_id: usize, rng: &mut ThreadRng) -> Vec<(String, LogColor)> {
+    let Some((id, name)) = e
+        .layout
+        .buildings
+        .iter()
+        .filter(|b| b.get_district_id() == district_id && b.get_building_type() != BuildingType::EmptySpace)
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .map(|b| (b.id, b.get_name().to_string()))
+    else {
+        return vec![];
+    };
+
+    let mut messages = vec![];
+    let district = e.population.get_district_mut(district_id).unwrap();
+    let dead = district.affect_alive(
+        rng,
+        |p| if p.building_uuid == Some(id) { FIRE_DEATH_CHANCE } else { 0.0 },
+        |people| {
+            if let Some(witness) = people.get_witness_name() {
+                messages.push((format!("{} died in the fire.", witness), LogColor::Important));
+            }
+            people.make_dead(CauseOfDeath::Fire);
+        },
+    );
+
+    let (homeless, _) = e.population.evict_building(&id);
+    e.layout.clear_building(&id);
+
+    messages.insert(
+        0,
+        (
+            format!("A fire destroyed {}, {} people died and {} are homeless", name, dead, homeless),
+            LogColor::Important,
+        ),
+    );
+    messages
+}
+
+/// Angry people kill some of their neighbours and break things, the city pays the damages.
+fn riot(e: &mut Engine, district_id: usize, rng: &mut ThreadRng) -> Vec<(String, LogColor)> {
+    let district = e.population.get_district_mut(district_id).unwrap();
+    let rioters: u32 =
+        district.peoples.iter().filter_map(|p| p.as_alive()).filter(|p| p.mood == Mood::Angry).map(|p| p.count).sum();
+
+    let dead = district.affect_alive(rng, |_| RIOT_DEATHS, |people| people.make_dead(CauseOfDeath::Murder));
+    let zone = district.zone_type;
+
+    let damages = rioters * RIOT_DAMAGES;
+    e.treasury.pay(damages);
+
+    vec![(
+        format!("Riots in the {} district, {} people died and the damages cost {}", zone, dead, damages),
+        LogColor::Important,
+    )]
+}
+
+/// Everyone gets happier, nine months later comes a baby boom.
+fn festival(e: &mut Engine, district_id: usize, rng: &mut ThreadRng) -> Vec<(String, LogColor)> {
+    e.treasury.pay(FESTIVAL_COST);
+
+    let district = e.population.get_district_mut(district_id).unwrap();
+    district.affect_alive(rng, |_| FESTIVAL_MOOD_CHANCE, |p| {
+        let person = p.as_alive_mut().unwrap();
+        person.mood = person.mood.to_average(Mood::Zealot);
+    });
+    let zone = district.zone_type;
+
+    e.events.schedule("Baby boom", district_id, FESTIVAL_BABY_BOOM_DELAY);
+
+    vec![(format!("The {} district celebrates its yearly festival", zone), LogColor::Unusual)]
+}
+
+/// Many couples have a baby at home at the same time.
+fn baby_boom(e: &mut Engine, district_id: usize, rng: &mut ThreadRng) -> Vec<(String, LogColor)> {
+    let balance = &e.balance.births;
+    let district = e.population.get_district_mut(district_id).unwrap();
+
+    let adults: Vec<(usize, &People)> = district
+        .peoples
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.get_legal_state() == PeopleLegalState::Adult && p.as_alive().is_some())
+        .collect();
+    let births = ((adults.iter().map(|(_, p)| p.get_count() as f64).sum::<f64>() * BABY_BOOM_BIRTHS).ceil() as usize).max(1);
+
+    // one couple per baby, the couples between the same two entries share a binomial part of the births
+    let couples = make_pairs(adults, rng);
+    let mut left_couples: u32 = couples.iter().map(|(_, count)| count).sum();
+    let mut left_births = (births as u32).min(left_couples);
+    let mut babies: Vec<People> = vec![];
+    for ((i, j), count) in couples {
+        let chance = left_births as f64 / left_couples as f64;
+        left_couples -= count;
+        let amount = binomial(rng, count, chance).clamp(left_births.saturating_sub(left_couples), count.min(left_births));
+        left_births -= amount;
+
+        let (parent1, parent2) = (district.peoples[i].as_alive().unwrap(), district.peoples[j].as_alive().unwrap());
+        babies.extend(spawn_childs(amount, 0, parent1, parent2, balance, rng));
+    }
+    let born: u32 = babies.iter().map(|b| b.get_count()).sum();
+    district.add_peoples(&mut babies);
+
+    vec![(format!("Baby boom in the {} district, {} babies were born", district.zone_type, born), LogColor::Unusual)]
+}
+
+/// The workers of the district bring in a bonus.
+fn economic_boom(e: &mut Engine, district_id: usize, _rng: &mut ThreadRng) -> Vec<(String, LogColor)> {
+    let earned = count_workers(e, district_id) as u32 * BOOM_BONUS;
+    e.treasury.receive(earned);
+
+    let zone = e.population.get_district(district_id).unwrap().zone_type;
+    vec![(format!("Economic boom in the {} district, the city earned {}", zone, earned), LogColor::Unusual)]
+}
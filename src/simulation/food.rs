@@ -0,0 +1,49 @@
+use crate::engine::core::LockableEngine;
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+
+/// Will forward one month of harvest \
+/// Every building grows food depending on the terrain under it (see `TerrainKind::food_production`)
+/// and its function, and feeds its own district. Starving districts have more deaths and fewer births.
+pub fn update_food(engine: &LockableEngine) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    let mut production = vec![0f32; e.population.num_districts];
+    for building in &e.layout.buildings {
+        if let Some(food) = production.get_mut(building.get_district_id()) {
+            *food += building
+                .get_area()
+                .iter()
+                .map(|(x, y)| e.layout.terrain_at(*x, *y).food_production())
+                .sum::<f32>()
+                * building.get_function().rules().food;
+        }
+    }
+
+    let mut starving = vec![];
+    for (district_id, food) in production.into_iter().enumerate() {
+        let district = e.population.get_district_mut(district_id).unwrap();
+        let needs = district.count_alive();
+
+        let was_fed = district.food_supply >= 1.0;
+        district.food_supply = if needs == 0 { 1.0 } else { food / needs as f32 };
+
+        if was_fed && district.food_supply < 1.0 {
+            starving.push(district.zone_type);
+        }
+    }
+
+    for zone in starving {
+        send_to_side_bar_auto!(
+            w,
+            &w,
+            format!("The {} district is running out of food", zone),
+            LogType::City,
+            LogColor::Important
+        );
+    }
+
+    lock_unlock!(w);
+}
@@ -0,0 +1,158 @@
+use crate::engine::layout::LayoutId;
+use crate::population::district::{DistrictZone, PopulationDistrict};
+use crate::population::people::{BasePeopleInfo, People, PeopleLegalState};
+use crate::population::cohort::binomial;
+
+use rand::Rng;
+
+/// Share of the people above the capacity of a district who leave it each month
+const MIGRATION_CHANCE: f64 = 0.1;
+
+/// A building people can live in
+pub struct Home {
+    pub id: LayoutId,
+    pub district_id: usize,
+    pub capacity: usize,
+}
+
+/// Core > Residentials > Industrials > Slums
+fn zone_rank(zone: DistrictZone) -> u8 {
+    match zone {
+        DistrictZone::Core => 3,
+        DistrictZone::Residentials => 2,
+        DistrictZone::Industrials => 1,
+        DistrictZone::Slums => 0,
+    }
+}
+
+/// Share of the people working
+fn work_percentage(district: &PopulationDistrict) -> f32 {
+    match district.num_people {
+        0 => 0.0,
+        num_people => district.working_poulation as f32 / num_people as f32,
+    }
+}
+
+/// A better zone, or the same zone with more happiness and work
+fn is_better(to: &PopulationDistrict, from: &PopulationDistrict) -> bool {
+    let conditions = |d: &PopulationDistrict| d.get_happiness_percentage() + work_percentage(d);
+    match zone_rank(to.zone_type).cmp(&zone_rank(from.zone_type)) {
+        std::cmp::Ordering::Equal => conditions(to) > conditions(from),
+        ordering => ordering.is_gt(),
+    }
+}
+
+/// Room left in each home of the district, in the order of `homes`
+fn free_homes(districts: &[PopulationDistrict], homes: &[Home], district_id: usize) -> Vec<(LayoutId, u32)> {
+    homes
+        .iter()
+        .filter(|home| home.district_id == district_id)
+        .map(|home| {
+            let occupants: usize = districts.iter().map(|d| d.get_occupants(&home.id)).sum();
+            (home.id, home.capacity.saturating_sub(occupants) as u32)
+        })
+        .filter(|(_, room)| *room > 0)
+        .collect()
+}
+
+/// The adults of the overcrowded districts leave for the best neighbor with better conditions
+/// and room in its homes, they lose their job on the way. \
+/// The districts are done in the order of their id, with the state left by the previous ones,
+/// so the same `rng` always gives the same moves. Return a message for each move.
+pub fn migrate(
+    districts: &mut [PopulationDistrict],
+    homes: &[Home],
+    linked: &[bool],
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let mut messages = vec![];
+
+    for from in 0..districts.len() {
+        let over = districts[from].count_alive().saturating_sub(districts[from].capacity as usize) as u32;
+        if !linked[from] || over == 0 {
+            continue;
+        }
+
+        let Some(to) = districts[from]
+            .neighbors
+            .iter()
+            .copied()
+            .filter(|to| linked[*to] && is_better(&districts[*to], &districts[from]))
+            .max_by(|a, b| {
+                zone_rank(districts[*a].zone_type)
+                    .cmp(&zone_rank(districts[*b].zone_type))
+                    .then(b.cmp(a))
+            })
+        else {
+            continue;
+        };
+
+        let mut rooms = free_homes(districts, homes, to);
+        let room: u32 = rooms.iter().map(|(_, room)| room).sum();
+        let leaving = binomial(rng, over, MIGRATION_CHANCE).min(room);
+        if leaving == 0 {
+            continue;
+        }
+
+        let mut migrants = leave(&mut districts[from], leaving, &mut rooms, rng);
+        let moved: u32 = migrants.iter().map(|p| p.get_count()).sum();
+        districts[to].add_peoples(&mut migrants);
+
+        messages.push(format!(
+            "{} people left the {} district for the {} district",
+            moved, districts[from].zone_type, districts[to].zone_type
+        ));
+    }
+
+    messages
+}
+
+/// Take `amount` adults out of the district, each cohort is drawn with a binomial share of the
+/// ones left and they are spread in the `rooms`.
+fn leave(district: &mut PopulationDistrict, amount: u32, rooms: &mut [(LayoutId, u32)], rng: &mut impl Rng) -> Vec<People> {
+    let adults: Vec<(usize, u32)> = district
+        .peoples
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.get_legal_state() == PeopleLegalState::Adult && p.as_alive().is_some())
+        .map(|(index, p)| (index, p.get_count()))
+        .collect();
+    let mut left_adults: u32 = adults.iter().map(|(_, count)| count).sum();
+    let mut left = amount.min(left_adults);
+
+    let mut migrants = vec![];
+    for (index, count) in adults {
+        let chance = left as f64 / left_adults as f64;
+        left_adults -= count;
+        let mut taken = binomial(rng, count, chance).clamp(left.saturating_sub(left_adults), count.min(left));
+        left -= taken;
+
+        let from_home = district.peoples[index].get_building_uuid().copied();
+        for (home, room) in rooms.iter_mut().filter(|(_, room)| *room > 0) {
+            if taken == 0 {
+                break;
+            }
+            let amount = taken.min(*room);
+            *room -= amount;
+            taken -= amount;
+
+            let mut part = district.peoples[index].clone();
+            let alive = part.as_alive_mut().unwrap();
+            alive.count = amount;
+            alive.building_uuid = Some(*home);
+            alive.workplace_uuid = None;
+            alive.work_status = None;
+            migrants.push(part);
+
+            district.peoples[index].as_alive_mut().unwrap().count -= amount;
+            district.move_occupants(from_home, None, amount as usize);
+            district.num_people -= amount as usize;
+        }
+    }
+
+    district.peoples.retain(|p| p.get_count() > 0);
+    district.recalcul_happiness();
+    district.recalcul_sickness();
+    district.recalcul_working_population();
+    migrants
+}
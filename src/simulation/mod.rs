@@ -1,6 +1,8 @@
 use crate::engine::core::{Engine, LockableEngine};
 use crate::engine::layout::{Layout, LayoutId};
-use crate::population::people::AlivePerson;
+use crate::engine::terrain::TerrainKind;
+use crate::population::people::{AlivePerson, CauseOfDeath};
+use crate::population::cohort::{binomial, PopulationModel};
 use crate::population::{self, Population};
 use crate::threads::sidebar::SideBarMessage;
 use crate::{
@@ -10,25 +12,43 @@ use crate::{
         district::PopulationDistrict,
         people::{BasePeopleInfo, People, PeopleLegalState},
     },
-    send_to_side_bar_auto,
     ui::sidebar::{LogColor, LogType},
 };
-use births::{number_of_children_to_make, spawn_childs};
-use deaths::check_death;
+use balance::{Balance, BirthBalance, DeathBalance};
+use births::{birth_probability, spawn_childs};
+use deaths::death_odds;
+use migration::{migrate, Home};
+use services::Services;
 use log::debug;
 use rand::seq::IndexedRandom;
 use rand::Rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use rand::{rng, rngs::ThreadRng, seq::SliceRandom};
-use std::collections::HashMap;
-use std::sync::mpsc::Sender;
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
 
+pub mod balance;
 pub mod births;
 pub mod deaths;
+pub mod diseases;
 pub mod dna_transmission;
+pub mod events;
+pub mod food;
+pub mod migration;
+pub mod monsters;
+pub mod services;
+pub mod traffic;
+pub mod treasury;
 
 /// Will forward one months \
-/// Kinda expensive, will do a DFS on the districts then shuffle the population to make babies.
+/// The districts linked to the core are aged, get their births and deaths in parallel, one
+/// worker thread each with its own random stream, on a snapshot of the population. Then the
+/// effects between districts are applied in the order of the districts (the witnesses to make,
+/// the messages, the migrations) and only the swap back into the engine takes the write lock. \
+/// If the population changed since the snapshot (the editor or an event), the month is done again
+/// on the current districts while holding the write lock, with the same seed.
 pub fn update_time_population(
     engine: &LockableEngine,
     birth_month: bool,
@@ -36,128 +56,287 @@ pub fn update_time_population(
     rng: &mut ThreadRng,
     debug: bool,
 ) {
-    lock_read!(engine |> pop);
-    let order = pop.population.num_districts;
-    lock_unlock!(pop);
-    let mut marks: Vec<bool> = vec![false; order];
-
-    update_district_peoples(
-        engine,
-        0,
-        birth_month,
-        witness_to_make,
-        &mut marks,
-        rng,
-        debug,
-    )
+    let seed: u64 = rng.random();
+
+    // ----- SNAPSHOT -----
+    lock_read!(engine |> read);
+    let generation = read.population.generation();
+    let (districts, context) = StepContext::snapshot(&read, birth_month, debug);
+    let side_bar = read.side_bar_tx.clone();
+    lock_unlock!(read);
+
+    let mut step = step_districts(districts, *witness_to_make, &context, seed);
+
+    // ----- SWAP -----
+    lock_write!(engine |> write);
+    if write.population.generation() != generation {
+        let (districts, context) = StepContext::snapshot(&write, birth_month, debug);
+        step = step_districts(districts, *witness_to_make, &context, seed);
+    }
+    write.population.swap_districts(step.districts);
+    lock_unlock!(write);
+
+    *witness_to_make = step.witness_to_make;
+    for message in step.messages {
+        let _ = side_bar.send(message);
+    }
 }
 
-fn update_district_peoples(
-    engine: &Arc<RwLock<Engine>>,
-    district_id: usize,
+/// The districts after one month, see `step_districts`
+struct MonthStep {
+    districts: Vec<PopulationDistrict>,
+    witness_to_make: u8,
+    messages: Vec<SideBarMessage>,
+}
+
+/// One month of `update_time_population` outside of the engine, the same `seed` gives the same month.
+fn step_districts(districts: Vec<PopulationDistrict>, witness_to_make: u8, context: &StepContext, seed: u64) -> MonthStep {
+    let linked = linked_to_core(&districts);
+    // the core district makes the missing witnesses, the ones it can't make wait for next month
+    let mut budgets = vec![0; districts.len()];
+    if let Some(budget) = budgets.first_mut() {
+        *budget = witness_to_make;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let seeds: Vec<u64> = districts.iter().map(|_| rng.random()).collect();
+
+    // ----- PARALLEL -----
+    let steps: Vec<DistrictStep> = thread::scope(|scope| {
+        let workers: Vec<_> = districts
+            .into_iter()
+            .zip(seeds)
+            .zip(budgets)
+            .map(|((district, seed), budget)| {
+                let linked = linked[district.id];
+                scope.spawn(move || match linked {
+                    true => update_district_peoples(district, budget, context, StdRng::seed_from_u64(seed)),
+                    false => DistrictStep::unchanged(district, budget),
+                })
+            })
+            .collect();
+
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    // ----- MERGE -----
+    let mut month = MonthStep {
+        districts: Vec::with_capacity(steps.len()),
+        witness_to_make: 0,
+        messages: vec![],
+    };
+    for step in steps {
+        month.witness_to_make = month.witness_to_make.saturating_add(step.witness_to_make);
+        month.messages.extend(step.messages.try_iter());
+        month.districts.push(step.district);
+    }
+
+    // ----- MIGRATION -----
+    for message in migrate(&mut month.districts, &context.homes, &linked, &mut rng) {
+        month.messages.push(SideBarMessage::Single(Box::new(message), LogType::City, LogColor::Normal));
+    }
+
+    month
+}
+
+/// What the districts need from the engine during `update_time_population`
+struct StepContext {
     birth_month: bool,
-    witness_to_make: &mut u8,
-    marked: &mut Vec<bool>,
-    rng: &mut ThreadRng,
+    model: PopulationModel,
+    services: Services,
+    balance: Balance,
+    /// Share of each home built on irradiated ground, see `irradiated_homes`
+    exposures: HashMap<LayoutId, f64>,
+    /// Where the migrants can live, see `migration::migrate`
+    homes: Vec<Home>,
     debug: bool,
-) {
-    lock_write!(engine |> pop);
-    if !marked[district_id] {
-        marked[district_id] = true;
-        let s = pop.side_bar_tx.clone();
+}
 
-        let district = pop.population.get_district_mut(district_id).unwrap();
+impl StepContext {
+    /// Copy of the districts and of everything their month depends on
+    fn snapshot(e: &Engine, birth_month: bool, debug: bool) -> (Vec<PopulationDistrict>, StepContext) {
+        let homes = e
+            .layout
+            .buildings
+            .iter()
+            .filter(|b| b.get_housing_capacity() > 0)
+            .map(|b| Home {
+                id: b.id,
+                district_id: b.get_district_id(),
+                capacity: b.get_housing_capacity(),
+            })
+            .collect();
 
-        district
-            .peoples
-            .iter_mut()
-            .filter_map(|p| p.as_alive_mut())
-            .for_each(|alive| {
-                alive.age_in_months += 1;
-                if let Some(name) = alive.get_witness_name() {
-                    if alive.age_in_months % 120 == 0 {
-                        let _ = s.clone().send(SideBarMessage::Single(
-                            Box::new(format!(
-                                "{} celebrate his {}th year!",
-                                name,
-                                alive.get_age()
-                            )),
-                            LogType::City,
-                            LogColor::Normal,
-                        ));
-                    }
-                }
-            });
+        let context = StepContext {
+            birth_month,
+            model: e.population.model,
+            services: e.services.clone(),
+            balance: e.balance.clone(),
+            exposures: irradiated_homes(&e.layout),
+            homes,
+            debug,
+        };
+        (e.population.snapshot_districts(), context)
+    }
+}
 
-        if birth_month {
-            update_births(s.clone(), district, witness_to_make, rng, debug);
+/// A district after one month, on its own
+struct DistrictStep {
+    district: PopulationDistrict,
+    /// Witnesses the district could not make plus the ones who died, to make next month
+    witness_to_make: u8,
+    messages: Receiver<SideBarMessage>,
+}
+
+impl DistrictStep {
+    /// The districts that are not linked to the core don't live
+    fn unchanged(district: PopulationDistrict, witness_to_make: u8) -> DistrictStep {
+        DistrictStep {
+            district,
+            witness_to_make,
+            messages: channel().1,
         }
-        *witness_to_make += update_deaths(s.clone(), district, debug);
+    }
+}
 
-        let clones = district.neighbors.clone();
-        // pop.refresh();
-        lock_unlock!(pop);
+fn update_district_peoples(
+    mut district: PopulationDistrict,
+    mut witness_to_make: u8,
+    context: &StepContext,
+    mut rng: StdRng,
+) -> DistrictStep {
+    let (s, messages) = channel();
 
-        if debug {
-            send_to_side_bar_auto!(
-                e,
-                engine,
-                "One year has passed",
-                LogType::City,
-                LogColor::Normal
-            );
-        }
+    district
+        .peoples
+        .iter_mut()
+        .filter_map(|p| p.as_alive_mut())
+        .for_each(|alive| {
+            alive.age_in_months += 1;
+            if let Some(name) = alive.get_witness_name() {
+                if alive.age_in_months % 120 == 0 {
+                    let _ = s.clone().send(SideBarMessage::Single(
+                        Box::new(format!(
+                            "{} celebrate his {}th year!",
+                            name,
+                            alive.get_age()
+                        )),
+                        LogType::City,
+                        LogColor::Normal,
+                    ));
+                }
+            }
+        });
+
+    if context.birth_month {
+        update_births(
+            s.clone(),
+            &mut district,
+            &context.services,
+            &context.balance.births,
+            &mut witness_to_make,
+            &mut rng,
+            context.debug,
+        );
+    }
+    witness_to_make = witness_to_make.saturating_add(update_deaths(
+        s.clone(),
+        &mut district,
+        &context.exposures,
+        &context.services,
+        &context.balance.deaths,
+        &mut rng,
+        context.debug,
+    ));
+    if context.model == PopulationModel::Cohort {
+        district.compact();
+    }
+
+    if context.debug {
+        let _ = s.send(SideBarMessage::Single(
+            Box::new("One year has passed"),
+            LogType::City,
+            LogColor::Normal,
+        ));
+    }
 
-        for district_id in clones {
-            update_district_peoples(
-                engine,
-                district_id,
-                birth_month,
-                witness_to_make,
-                marked,
-                rng,
-                debug,
-            );
+    DistrictStep {
+        district,
+        witness_to_make,
+        messages,
+    }
+}
+
+/// The districts reached from the core through their neighbors
+fn linked_to_core(districts: &[PopulationDistrict]) -> Vec<bool> {
+    let mut marked = vec![false; districts.len()];
+    let mut stack = vec![0];
+
+    while let Some(id) = stack.pop() {
+        if id >= districts.len() || marked[id] {
+            continue;
         }
+        marked[id] = true;
+        stack.extend(districts[id].neighbors.iter().copied());
     }
+
+    marked
+}
+
+/// Share of each building built on irradiated ground
+fn irradiated_homes(layout: &Layout) -> HashMap<LayoutId, f64> {
+    let Some(terrain) = &layout.terrain else {
+        return HashMap::new();
+    };
+
+    layout
+        .buildings
+        .iter()
+        .map(|b| (b.id, terrain.share_of(&b.get_area(), TerrainKind::Irradiated)))
+        .collect()
 }
 
 /// Will shuffle the district's population because of the parents
-fn update_births(
+pub(crate) fn update_births(
     pipe: Sender<SideBarMessage>,
     district: &mut PopulationDistrict,
+    services: &Services,
+    balance: &BirthBalance,
     witness_to_make: &mut u8,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
     debug: bool,
 ) {
     let mut childs: Vec<People> = make_pairs(
         district
             .peoples
             .iter()
-            .filter(|p| p.get_legal_state() != PeopleLegalState::Child && p.as_alive().is_some())
+            .enumerate()
+            .filter(|(_, p)| p.get_legal_state() != PeopleLegalState::Child && p.as_alive().is_some())
             .collect(),
         rng,
     )
-    .iter()
-    .map(|(parent1, parent2)| {
-        let kids = spawn_childs(
-            number_of_children_to_make(parent1.as_alive().unwrap(), district),
-            if *witness_to_make > 0 {
-                *witness_to_make -= 1;
-                true
-            } else {
-                false
-            },
+    .into_iter()
+    .map(|((i, j), couples)| {
+        let (parent1, parent2) = (&district.peoples[i], &district.peoples[j]);
+        let probability = birth_probability(
             parent1.as_alive().unwrap(),
-            parent2.as_alive().unwrap(),
+            district,
+            parent1.get_building_uuid().map_or(0.0, |home| services.get_levels(home).education),
+            balance,
         );
-        if kids.len() > 0 {
+        // every couple has the integer part, a binomial share of them has one more
+        let amount = couples * probability.floor() as u32 + binomial(rng, couples, probability.fract());
+        let witnesses = (*witness_to_make as u32).min(couples) as u8;
+        *witness_to_make -= witnesses;
+
+        let kids = spawn_childs(amount, witnesses, parent1.as_alive().unwrap(), parent2.as_alive().unwrap(), balance, rng);
+        let born: u32 = kids.iter().map(|k| k.get_count()).sum();
+        if born > 0 {
             if parent1.is_witness() {
                 let _ = pipe.send(SideBarMessage::Single(
                     Box::new(format!(
                         "{} had {} child",
                         parent1.get_witness_name().unwrap(),
-                        kids.len()
+                        born
                     )),
                     LogType::City,
                     LogColor::Normal,
@@ -167,7 +346,7 @@ fn update_births(
                     Box::new(format!(
                         "{} had {} child",
                         parent2.get_witness_name().unwrap(),
-                        kids.len()
+                        born
                     )),
                     LogType::City,
                     LogColor::Normal,
@@ -182,7 +361,7 @@ fn update_births(
 
     if debug {
         let _ = pipe.send(SideBarMessage::Single(
-            Box::new(format!("Births: {}", childs.len())),
+            Box::new(format!("Births: {}", childs.iter().map(|c| c.get_count()).sum::<u32>())),
             LogType::City,
             LogColor::Normal,
         ));
@@ -191,49 +370,80 @@ fn update_births(
     district.add_peoples(&mut childs);
 }
 
-fn update_deaths(
+pub(crate) fn update_deaths(
     pipe: Sender<SideBarMessage>,
     district: &mut PopulationDistrict,
+    exposures: &HashMap<LayoutId, f64>,
+    services: &Services,
+    balance: &DeathBalance,
+    rng: &mut impl Rng,
     debug: bool,
 ) -> u8 {
     let zone = district.zone_type.clone();
     let happiness: f64 = district.get_happiness_percentage().into();
+    let food_supply: f64 = district.food_supply.into();
 
     let bef = district.get_population_number_by(PeopleLegalState::Dead);
 
     let mut witness_to_make = 0;
+    // the dead members split from the cohorts
+    let mut corpses: Vec<People> = vec![];
+    // (home, dead) to take out of the occupancy
+    let mut vacated: Vec<(Option<LayoutId>, usize)> = vec![];
 
     district.peoples.retain(|people| people.as_alive() != None); // clear corpse
     district.peoples.iter_mut().for_each(|people| {
-        let mut rng = rng();
-        if let Some((cause, _)) = check_death(people.as_alive().unwrap(), zone, happiness, &mut rng)
-        {
-            if people.is_witness() {
-                let _ = pipe.send(SideBarMessage::Single(
-                    Box::new(format!(
-                        "{} died {} at {}y.",
-                        people.get_witness_name().unwrap(),
-                        match cause {
-                            crate::population::people::CauseOfDeath::OldAge => "of old age",
-                            crate::population::people::CauseOfDeath::Murder => "killed by someone",
-                            crate::population::people::CauseOfDeath::Sickness => "of a disease",
-                            crate::population::people::CauseOfDeath::Radiations => "of radiations",
-                            crate::population::people::CauseOfDeath::WorkAccident =>
-                                "of a work accident",
-                            crate::population::people::CauseOfDeath::EatenByMonster =>
-                                "eaten by a monster",
-                            crate::population::people::CauseOfDeath::Poverty => "because of hunger",
-                        },
-                        people.get_age()
-                    )),
-                    LogType::City,
-                    LogColor::Important,
-                ));
-                witness_to_make += 1;
+        let exposure = people.get_building_uuid().and_then(|id| exposures.get(id)).copied().unwrap_or(0.0);
+
+        let levels = people.get_building_uuid().map(|home| services.get_levels(home)).unwrap_or_default();
+
+        let odds = death_odds(people.as_alive().unwrap(), zone, happiness, food_supply, exposure, levels, balance);
+        let dead = binomial(rng, people.get_count(), odds.probability);
+        if dead == 0 {
+            return;
+        }
+
+        let mut causes: Vec<(CauseOfDeath, u32)> = vec![];
+        for _ in 0..dead {
+            let cause = odds.pick_cause(rng);
+            match causes.iter_mut().find(|(c, _)| *c == cause) {
+                Some((_, n)) => *n += 1,
+                None => causes.push((cause, 1)),
             }
-            people.make_dead(cause);
+        }
+
+        if people.is_witness() {
+            let _ = pipe.send(SideBarMessage::Single(
+                Box::new(format!(
+                    "{} died {} at {}y.",
+                    people.get_witness_name().unwrap(),
+                    match causes[0].0 {
+                        CauseOfDeath::OldAge => "of old age",
+                        CauseOfDeath::Murder => "killed by someone",
+                        CauseOfDeath::Sickness => "of a disease",
+                        CauseOfDeath::Radiations => "of radiations",
+                        CauseOfDeath::WorkAccident => "of a work accident",
+                        CauseOfDeath::EatenByMonster => "eaten by a monster",
+                        CauseOfDeath::Poverty => "because of hunger",
+                        CauseOfDeath::Fire => "in a fire",
+                    },
+                    people.get_age()
+                )),
+                LogType::City,
+                LogColor::Important,
+            ));
+            witness_to_make += 1;
+        }
+
+        vacated.push((people.get_building_uuid().copied(), dead as usize));
+        for (cause, amount) in causes {
+            corpses.extend(people.with_members(amount, |p| p.make_dead(cause)));
         }
     });
+    district.peoples.append(&mut corpses);
+    for (home, dead) in vacated {
+        district.move_occupants(home, None, dead);
+    }
 
     if debug {
         let _ = pipe.send(SideBarMessage::Single(
@@ -248,11 +458,14 @@ fn update_deaths(
     witness_to_make
 }
 
-/// Ensure that the peoples are grouped by building uuid and only get selected once
-fn make_pairs(people: Vec<&People>, rng: &mut ThreadRng) -> Vec<(People, People)> {
-    let mut building_groups: HashMap<LayoutId, Vec<&People>> = HashMap::new();
+/// Ensure that the peoples are grouped by building uuid and only get selected once. \
+/// The cohorts are paired by count, each member of a cohort takes a partner in its own cohort or
+/// in another one of the building with a chance that follows their sizes. Return how many couples
+/// there are between each two entries, in the same order for the same `rng`.
+pub(crate) fn make_pairs(people: Vec<(usize, &People)>, rng: &mut impl Rng) -> Vec<((usize, usize), u32)> {
+    let mut building_groups: BTreeMap<LayoutId, Vec<(usize, u32)>> = BTreeMap::new();
 
-    for person in people {
+    for (index, person) in people {
         building_groups
             .entry(
                 person
@@ -261,18 +474,45 @@ fn make_pairs(people: Vec<&People>, rng: &mut ThreadRng) -> Vec<(People, People)
                     .clone(),
             )
             .or_default()
-            .push(person);
+            .push((index, person.get_count()));
     }
 
-    let mut pairs = Vec::new();
+    let mut pairs = vec![];
 
     for (_building_uuid, guys_in_the_building) in building_groups.iter_mut() {
         guys_in_the_building.shuffle(rng);
 
-        while guys_in_the_building.len() >= 2 {
-            let p1 = guys_in_the_building.pop().unwrap();
-            let p2 = guys_in_the_building.pop().unwrap();
-            pairs.push((p1.clone(), p2.clone()));
+        for a in 0..guys_in_the_building.len() {
+            let index = guys_in_the_building[a].0;
+            let single = std::mem::take(&mut guys_in_the_building[a].1);
+            let mut others: u32 = guys_in_the_building[a + 1..].iter().map(|(_, count)| count).sum();
+            if single == 0 {
+                continue;
+            }
+
+            // the members who don't find a partner among the others stay together
+            let mut outside = binomial(rng, single, others as f64 / (others + single - 1).max(1) as f64).min(others);
+            if (single - outside) % 2 == 1 && outside < others {
+                outside += 1;
+            }
+            if single - outside >= 2 {
+                pairs.push(((index, index), (single - outside) / 2));
+            }
+
+            for (partner, count) in guys_in_the_building[a + 1..].iter_mut() {
+                if outside == 0 {
+                    break;
+                }
+                let chance = *count as f64 / others as f64;
+                others -= *count;
+                // the cohorts after this one can't take more than their members
+                let couples = binomial(rng, outside, chance).clamp(outside.saturating_sub(others), outside.min(*count));
+                if couples > 0 {
+                    pairs.push(((index, *partner), couples));
+                    *count -= couples;
+                    outside -= couples;
+                }
+            }
         }
     }
 
@@ -282,48 +522,134 @@ fn make_pairs(people: Vec<&People>, rng: &mut ThreadRng) -> Vec<(People, People)
 pub fn update_people_in_building(engine: &LockableEngine, rng: &mut ThreadRng) {
     lock_read!(engine |> read);
     let buildings = read.layout.get_buildings();
-    let (full_buildings, empty_buildings): (Vec<_>, Vec<_>) = buildings
-        .iter()
-        .partition(|&b| b.is_overcrowded(&read.population));
+    let full_buildings: Vec<_> = buildings.iter().filter(|b| b.is_overcrowded(&read.population)).collect();
+    let empty_buildings: Vec<_> = buildings.iter().filter(|b| b.has_room(&read.population)).collect();
 
     let graph = read.layout.graph.clone();
     lock_unlock!(read);
 
     lock_write!(engine |> write);
-    for people in write
-        .population
-        .get_core_district_mut()
-        .peoples
-        .iter_mut()
-        .filter_map(|p| p.as_alive_mut())
-        .filter(|p| {
-            p.building_uuid.is_none()
-                || full_buildings
-                    .iter()
-                    .any(|b| b.get_building_uuid() == p.building_uuid.unwrap())
-                    && rng.random_bool(0.2)
-        })
-    {
-        if let Some(ref g) = graph {
-            let conexions = g.get_buildings_connections(people.building_uuid.unwrap());
-            debug!("NUMBER of conex: {}", conexions.len());
-
-            let target = conexions.get(0);
-
-            if let Some(building_id) = target {
-                for building in empty_buildings
-                    .iter()
-                    .filter(|b| b.get_building_uuid() == *building_id)
-                {
-                    people.building_uuid = Some(building.id);
-                }
-            } else {
-                if let Some(building) = empty_buildings.choose(&mut rand::rng()) {
-                    people.building_uuid = Some(building.get_building_uuid());
-                }
+    // the members of the cohorts who moved out
+    let mut movers: Vec<People> = vec![];
+    // (from, to, amount) of each move
+    let mut moves = vec![];
+    for people in write.population.get_core_district_mut().peoples.iter_mut() {
+        let Some(alive) = people.as_alive() else {
+            continue;
+        };
+        let moving = match alive.building_uuid {
+            None => alive.count,
+            Some(home) if full_buildings.iter().any(|b| b.get_building_uuid() == home) => {
+                binomial(rng, alive.count, 0.2)
             }
+            Some(_) => 0,
+        };
+        if moving == 0 {
+            continue;
         }
+
+        // homeless peoples have no connections and take any empty building
+        let conexions = alive
+            .building_uuid
+            .map(|id| graph.get_buildings_connections(id))
+            .unwrap_or_default();
+        debug!("NUMBER of conex: {}", conexions.len());
+
+        let target = conexions.get(0);
+
+        let new_home = if let Some(building_id) = target {
+            empty_buildings
+                .iter()
+                .find(|b| b.get_building_uuid() == *building_id)
+                .map(|b| b.id)
+        } else {
+            empty_buildings.choose(&mut rand::rng()).map(|b| b.get_building_uuid())
+        };
+
+        if let Some(home) = new_home {
+            moves.push((alive.building_uuid, Some(home), moving as usize));
+            movers.extend(people.with_members(moving, |p| {
+                if let Some(alive) = p.as_alive_mut() {
+                    alive.building_uuid = Some(home);
+                }
+            }));
+        }
+    }
+    let core = write.population.get_core_district_mut();
+    core.peoples.append(&mut movers);
+    for (from, to, amount) in moves {
+        core.move_occupants(from, to, amount);
     }
 
     lock_unlock!(write);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::district::DistrictZone;
+    use crate::population::dna::DNA;
+    use crate::population::people::Mood;
+
+    fn district(id: usize, zone_type: DistrictZone, size: u32, capacity: u16, neighbors: Vec<usize>, home: LayoutId) -> PopulationDistrict {
+        let mut rng = StdRng::seed_from_u64(id as u64);
+        let peoples: Vec<People> = (0..size)
+            .map(|i| {
+                let mut people = People::create_people(DNA::new(), Mood::Neutral, Some(home), false, &mut rng);
+                people.as_alive_mut().unwrap().age_in_months = 18 + i % 40;
+                people
+            })
+            .collect();
+
+        let mut district = PopulationDistrict {
+            id,
+            num_people: peoples.len(),
+            peoples,
+            zone_type,
+            capacity,
+            max_num_neighbors: 2,
+            neighbors,
+            num_happiness: 0,
+            num_sick: 0,
+            working_poulation: 0,
+            food_supply: 1.0,
+            occupants: HashMap::new(),
+        };
+        district.recalcul_occupancy();
+        district
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_month() {
+        let homes: Vec<LayoutId> = (0..3).map(|_| LayoutId::random()).collect();
+        // the overcrowded slums are next to the core and the residentials
+        let districts = vec![
+            district(0, DistrictZone::Core, 50, 1000, vec![1], homes[0]),
+            district(1, DistrictZone::Slums, 400, 100, vec![0, 2], homes[1]),
+            district(2, DistrictZone::Residentials, 50, 1000, vec![1], homes[2]),
+        ];
+        let context = StepContext {
+            birth_month: true,
+            model: PopulationModel::Individual,
+            services: Services::default(),
+            balance: Balance::default(),
+            exposures: HashMap::new(),
+            homes: homes
+                .iter()
+                .enumerate()
+                .map(|(district_id, id)| Home { id: *id, district_id, capacity: 1000 })
+                .collect(),
+            debug: false,
+        };
+
+        let first = step_districts(districts.clone(), 3, &context, 42);
+        let second = step_districts(districts.clone(), 3, &context, 42);
+        assert_eq!(first.districts, second.districts);
+        assert_eq!(first.witness_to_make, second.witness_to_make);
+
+        // the migrants went to the best zone and live in its homes
+        let core = &first.districts[0];
+        assert!(core.count_alive() > districts[0].count_alive() + 10);
+        assert_eq!(core.count_alive(), core.get_occupants(&homes[0]));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::engine::core::{Engine, LockableEngine};
+use crate::engine::layout::{Building, BuildingType, Defense, Layout, LayoutId, TERMINAL_RATIO};
+use crate::population::people::{BasePeopleInfo, CauseOfDeath};
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+use rand::rngs::ThreadRng;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Chance each month for a monster to come out of the wasteland
+const SPAWN_CHANCE: f64 = 0.01;
+/// Cells walked each month, (min, max)
+const MONSTER_SPEED: (usize, usize) = (1, 2);
+/// Share of the road cells, the furthest from the centre, where monsters can appear
+const EDGE_SHARE: f64 = 0.1;
+/// Distance in rows (or `TERMINAL_RATIO` columns) at which a guard post protects a building
+const GUARD_RANGE: i16 = 12;
+const GUARD_REPEL_CHANCE: f64 = 0.6;
+/// A monster walking this close to a wall can't go further
+const WALL_RANGE: i16 = 2;
+/// Chance for each person in the attacked building to be eaten
+const EATEN_CHANCE: f64 = 0.3;
+
+/// Cells next to a road cell a building can be reached from
+const NEIGHBOURS: [(i16, i16); 6] = [(-2, 0), (-1, 0), (1, 0), (2, 0), (0, -1), (0, 1)];
+
+#[derive(Debug, Clone)]
+pub struct Monster {
+    pub x: i16,
+    pub y: i16,
+    /// Remaining road cells to the target, the next one is last
+    path: Vec<(i16, i16)>,
+    target: LayoutId,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Monsters {
+    active: Vec<Monster>,
+}
+
+impl Monsters {
+    pub fn get_active(&self) -> &[Monster] {
+        &self.active
+    }
+}
+
+enum Outcome {
+    Walking,
+    StoppedByWall(String),
+    Repelled(String),
+    Attack(LayoutId),
+    /// The target was destroyed on the way
+    Lost,
+}
+
+/// Will forward one month of monster incursions \
+/// Monsters appear on the roads at the edge of the city and walk to the nearest building,
+/// walls stop them on the way and guard posts may repel them. Otherwise they eat some of
+/// the people living in the building they reach.
+pub fn update_monsters(engine: &LockableEngine, rng: &mut ThreadRng) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    let mut messages: Vec<String> = vec![];
+    let was_active = !e.monsters.active.is_empty();
+
+    if rng.random_bool(SPAWN_CHANCE) {
+        if let Some(monster) = spawn_monster(&e.layout, rng) {
+            messages.push("A monster came out of the wasteland".to_string());
+            e.monsters.active.push(monster);
+        }
+    }
+
+    let mut attacks = vec![];
+    let mut monsters = std::mem::take(&mut e.monsters.active);
+    monsters.retain_mut(|monster| match walk(monster, &e.layout, rng) {
+        Outcome::Walking => true,
+        Outcome::StoppedByWall(name) => {
+            messages.push(format!("A monster was stopped by the wall {}", name));
+            false
+        }
+        Outcome::Repelled(name) => {
+            messages.push(format!("The guards of {} repelled a monster", name));
+            false
+        }
+        Outcome::Attack(target) => {
+            attacks.push(target);
+            false
+        }
+        Outcome::Lost => false,
+    });
+    e.monsters.active = monsters;
+
+    for target in attacks {
+        let Some(building) = e.layout.buildings.iter().find(|b| b.id == target).cloned() else {
+            continue;
+        };
+        let (eaten, witnesses) = attack_building(e, &building, rng);
+        messages.push(format!("A monster attacked {}, {} people were eaten", building.get_name(), eaten));
+        for witness in witnesses {
+            messages.push(format!("{} was eaten by a monster.", witness));
+        }
+    }
+
+    // monsters are drawn over the map, it has to follow them
+    if was_active || !e.monsters.active.is_empty() {
+        e.refresh();
+    }
+
+    for message in messages {
+        send_to_side_bar_auto!(w, &w, message, LogType::Event, LogColor::Important);
+    }
+
+    lock_unlock!(w);
+}
+
+/// Pick a road cell at the edge of the city and find the way to the closest building. \
+/// None if there is no road or no building can be reached.
+fn spawn_monster(layout: &Layout, rng: &mut ThreadRng) -> Option<Monster> {
+    let (min_x, min_y, max_x, max_y) = layout.bounds()?;
+    let (center_x, center_y) = ((min_x + max_x) / 2, (min_y + max_y) / 2);
+
+    let road_cells: HashSet<(i16, i16)> = layout.roads.iter().flat_map(|r| r.get_area()).collect();
+
+    let mut edge: Vec<(i16, i16)> = road_cells.iter().copied().collect();
+    edge.sort_by_key(|(x, y)| {
+        (std::cmp::Reverse(((x - center_x) / TERMINAL_RATIO as i16).abs().max((y - center_y).abs())), *x, *y)
+    });
+    edge.truncate(((edge.len() as f64 * EDGE_SHARE).ceil() as usize).max(1));
+    let &(x, y) = edge.choose(rng)?;
+
+    let (path, target) = path_to_nearest_building(layout, &road_cells, (x, y))?;
+
+    Some(Monster { x, y, path, target })
+}
+
+/// Breadth first search on the road cells, stops on the first cell next to a building. \
+/// Return the path (reversed, without the start) and the building.
+fn path_to_nearest_building(
+    layout: &Layout,
+    road_cells: &HashSet<(i16, i16)>,
+    start: (i16, i16),
+) -> Option<(Vec<(i16, i16)>, LayoutId)> {
+    let mut building_cells: HashMap<(i16, i16), LayoutId> = HashMap::new();
+    for building in layout.buildings.iter().filter(|b| b.get_building_type() != BuildingType::EmptySpace) {
+        for cell in building.get_area() {
+            building_cells.insert(cell, building.id);
+        }
+    }
+
+    let mut parents: HashMap<(i16, i16), (i16, i16)> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    parents.insert(start, start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let next_to = NEIGHBOURS.iter().find_map(|(dx, dy)| building_cells.get(&(x + dx, y + dy)));
+        if let Some(target) = next_to {
+            let mut path = vec![];
+            let mut cell = (x, y);
+            while cell != start {
+                path.push(cell);
+                cell = parents[&cell];
+            }
+            return Some((path, *target));
+        }
+
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let cell = (x + dx, y + dy);
+            if road_cells.contains(&cell) && !parents.contains_key(&cell) {
+                parents.insert(cell, (x, y));
+                queue.push_back(cell);
+            }
+        }
+    }
+
+    None
+}
+
+/// Move the monster for one month and tell what happened to it
+fn walk(monster: &mut Monster, layout: &Layout, rng: &mut ThreadRng) -> Outcome {
+    let Some(target) = layout.buildings.iter().find(|b| b.id == monster.target) else {
+        return Outcome::Lost;
+    };
+
+    for _ in 0..rng.random_range(MONSTER_SPEED.0..=MONSTER_SPEED.1) {
+        let wall = layout
+            .buildings
+            .iter()
+            .filter(|b| b.get_defense() == Some(Defense::Wall))
+            .find(|b| distance_to(b, monster.x, monster.y) <= WALL_RANGE);
+        if let Some(wall) = wall {
+            return Outcome::StoppedByWall(wall.get_name().to_string());
+        }
+
+        match monster.path.pop() {
+            Some((x, y)) => (monster.x, monster.y) = (x, y),
+            None => break,
+        }
+    }
+
+    if !monster.path.is_empty() {
+        return Outcome::Walking;
+    }
+
+    let guards = layout
+        .buildings
+        .iter()
+        .filter(|b| b.get_defense() == Some(Defense::Guard))
+        .find(|b| distance_to(b, monster.x, monster.y) <= GUARD_RANGE);
+    match guards {
+        Some(guards) if rng.random_bool(GUARD_REPEL_CHANCE) => Outcome::Repelled(guards.get_name().to_string()),
+        _ => Outcome::Attack(target.id),
+    }
+}
+
+/// Kill some of the people living in the building, the `monster_escape` effect of the traits gives a chance to run away. \
+/// Return the number of people eaten and the names of the witnesses among them.
+fn attack_building(e: &mut Engine, building: &Building, rng: &mut ThreadRng) -> (usize, Vec<String>) {
+    let mut witnesses = vec![];
+
+    let Some(district) = e.population.get_district_mut(building.get_district_id()) else {
+        return (0, witnesses);
+    };
+
+    let eaten = district.affect_alive(
+        rng,
+        |alive| {
+            if alive.building_uuid == Some(building.id) {
+                EATEN_CHANCE * (1.0 - alive.dna.effects().monster_escape.clamp(0.0, 1.0))
+            } else {
+                0.0
+            }
+        },
+        |people| {
+            if let Some(name) = people.get_witness_name() {
+                witnesses.push(name.clone());
+            }
+            people.make_dead(CauseOfDeath::EatenByMonster);
+        },
+    );
+
+    (eaten as usize, witnesses)
+}
+
+/// Distance from the cell to the closest cell of the building, columns count for `1 / TERMINAL_RATIO`
+fn distance_to(building: &Building, x: i16, y: i16) -> i16 {
+    building
+        .get_area()
+        .iter()
+        .map(|(bx, by)| ((bx - x) / TERMINAL_RATIO as i16).abs().max((by - y).abs()))
+        .min()
+        .unwrap_or(i16::MAX)
+}
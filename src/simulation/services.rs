@@ -0,0 +1,154 @@
+use crate::engine::core::LockableEngine;
+use crate::engine::layout::LayoutId;
+use crate::population::people::{BasePeopleInfo, PeopleLegalState};
+use crate::ui::colors::{A_ALERT_COLOR, A_LIGHT_COLOR, A_SAND_COLOR};
+use crate::{lock_write, lock_unlock};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceKind {
+    Healthcare,
+    Education,
+    Policing,
+}
+
+impl ServiceKind {
+    /// None -> Healthcare -> Education -> Policing -> None
+    pub fn next(kind: Option<ServiceKind>) -> Option<ServiceKind> {
+        match kind {
+            None => Some(ServiceKind::Healthcare),
+            Some(ServiceKind::Healthcare) => Some(ServiceKind::Education),
+            Some(ServiceKind::Education) => Some(ServiceKind::Policing),
+            Some(ServiceKind::Policing) => None,
+        }
+    }
+}
+
+impl Display for ServiceKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ServiceKind::Healthcare => write!(f, "healthcare"),
+            ServiceKind::Education => write!(f, "education"),
+            ServiceKind::Policing => write!(f, "policing"),
+        }
+    }
+}
+
+/// Share of the people of a building the services around can take care of, from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ServiceLevels {
+    /// Beds in the hospitals over the people they cover
+    pub healthcare: f32,
+    /// Seats in the schools over the children they cover
+    pub education: f32,
+    pub policing: f32,
+}
+
+impl ServiceLevels {
+    pub fn get(&self, kind: ServiceKind) -> f32 {
+        match kind {
+            ServiceKind::Healthcare => self.healthcare,
+            ServiceKind::Education => self.education,
+            ServiceKind::Policing => self.policing,
+        }
+    }
+
+    fn add(&mut self, kind: ServiceKind, level: f32) {
+        let service = match kind {
+            ServiceKind::Healthcare => &mut self.healthcare,
+            ServiceKind::Education => &mut self.education,
+            ServiceKind::Policing => &mut self.policing,
+        };
+        *service = (*service + level).min(1.0);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Services {
+    /// Levels of every building covered by at least one service, computed by `update_services`
+    coverage: HashMap<LayoutId, ServiceLevels>,
+}
+
+impl Services {
+    /// Nothing for buildings out of reach of every service (and the homeless)
+    pub fn get_levels(&self, building_id: &LayoutId) -> ServiceLevels {
+        self.coverage.get(building_id).copied().unwrap_or_default()
+    }
+
+    /// Colour of the building in the coverage overlay, the gaps are in red
+    pub fn color(&self, building_id: &LayoutId, kind: ServiceKind) -> ansi_term::Color {
+        match self.get_levels(building_id).get(kind) {
+            0.0 => A_ALERT_COLOR,
+            l if l < 1.0 => A_SAND_COLOR,
+            _ => A_LIGHT_COLOR,
+        }
+    }
+
+    /// Number of buildings out of reach of this service
+    pub fn count_gaps(&self, buildings: &[LayoutId], kind: ServiceKind) -> usize {
+        buildings.iter().filter(|id| self.get_levels(id).get(kind) == 0.0).count()
+    }
+}
+
+/// Will forward one month of public services \
+/// Hospitals, schools and police stations cover the buildings they can reach by road within the
+/// range of their function (see `FunctionRules`). Each one shares its capacity between the
+/// people of the buildings it covers, a building covered by several gets the sum of their levels.
+pub fn update_services(engine: &LockableEngine) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    // (residents, children) of each building
+    let mut residents: HashMap<LayoutId, (usize, usize)> = HashMap::new();
+    for district_id in 0..e.population.num_districts {
+        for people in &e.population.get_district(district_id).unwrap().peoples {
+            if let (Some(home), Some(alive)) = (people.get_building_uuid(), people.as_alive()) {
+                let count = residents.entry(*home).or_default();
+                count.0 += alive.count as usize;
+                if people.get_legal_state() == PeopleLegalState::Child {
+                    count.1 += alive.count as usize;
+                }
+            }
+        }
+    }
+
+    let mut coverage: HashMap<LayoutId, ServiceLevels> = HashMap::new();
+    for building in &e.layout.buildings {
+        let rules = building.get_function().rules();
+        if rules.range == 0 {
+            continue;
+        }
+
+        let cells = building.get_area().len();
+        let covered = e.layout.graph.buildings_within(&building.id, rules.range);
+
+        for (kind, capacity) in [
+            (ServiceKind::Healthcare, rules.patients),
+            (ServiceKind::Education, rules.pupils),
+            (ServiceKind::Policing, rules.patrols),
+        ] {
+            if capacity == 0 {
+                continue;
+            }
+
+            let needs: usize = covered
+                .keys()
+                .filter_map(|id| residents.get(id))
+                .map(|(all, children)| if kind == ServiceKind::Education { *children } else { *all })
+                .sum();
+            let level = match needs {
+                0 => 1.0,
+                needs => ((capacity * cells) as f32 / needs as f32).min(1.0),
+            };
+
+            for id in covered.keys() {
+                coverage.entry(*id).or_default().add(kind, level);
+            }
+        }
+    }
+
+    e.services = Services { coverage };
+
+    lock_unlock!(w);
+}
@@ -0,0 +1,192 @@
+use crate::engine::core::LockableEngine;
+use crate::engine::layout::{LayoutId, Road};
+use crate::population::cohort::binomial;
+use crate::population::people::{Mood, PeopleLegalState, WorkLethality};
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::colors::{A_GREY_BLUE_COLOR, A_LIGHT_COLOR, A_RUST_COLOR_1, A_SAND_COLOR};
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+use rand::rngs::ThreadRng;
+use rand::seq::IndexedRandom;
+use std::collections::HashMap;
+
+/// Chance each month for someone stuck in traffic to get angrier (or calmer once it's over)
+const MOOD_CHANGE_CHANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Default)]
+pub struct Traffic {
+    /// Commuters that drove on each road during the last month
+    load: HashMap<LayoutId, u32>,
+    congested_roads: usize,
+}
+
+impl Traffic {
+    pub fn get_load(&self, road_id: &LayoutId) -> u32 {
+        self.load.get(road_id).copied().unwrap_or(0)
+    }
+
+    /// Load over capacity, the road is congested above 1.0
+    pub fn get_congestion(&self, road: &Road) -> f32 {
+        self.get_load(&road.id) as f32 / road.get_class().capacity() as f32
+    }
+
+    pub fn get_congested_roads(&self) -> usize {
+        self.congested_roads
+    }
+
+    /// Colour of the road in the traffic overlay
+    pub fn color(&self, road: &Road) -> ansi_term::Color {
+        match self.get_congestion(road) {
+            0.0 => A_GREY_BLUE_COLOR,
+            c if c < 0.5 => A_LIGHT_COLOR,
+            c if c <= 1.0 => A_SAND_COLOR,
+            _ => A_RUST_COLOR_1,
+        }
+    }
+}
+
+/// Will forward one month of commute \
+/// Adults without a job get one in a building they can reach from home, then every worker
+/// drives to work using `Graph::shortest_path`. Congested commutes make people unhappy and
+/// tired at work (see `deaths::death_odds`).
+pub fn update_commute(engine: &LockableEngine, rng: &mut ThreadRng) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+    let layout = &e.layout;
+
+    // (jobs, risk) of every building people can work in
+    let workplaces: HashMap<LayoutId, (usize, WorkLethality)> = layout
+        .buildings
+        .iter()
+        .filter(|b| b.get_job_capacity() > 0)
+        .map(|b| (b.id, (b.get_job_capacity(), b.get_function().rules().work_risk)))
+        .collect();
+    let mut workers: HashMap<LayoutId, usize> = HashMap::new();
+
+    let mut reachable_jobs: HashMap<LayoutId, Vec<LayoutId>> = HashMap::new();
+    let mut routes: HashMap<(LayoutId, LayoutId), Option<Vec<LayoutId>>> = HashMap::new();
+    let mut load: HashMap<LayoutId, u32> = HashMap::new();
+    // (district, index of the person, home, work)
+    let mut commuters = vec![];
+
+    for district_id in 0..e.population.num_districts {
+        let district = e.population.get_district_mut(district_id).unwrap();
+
+        // the members of a cohort who could not keep or find the same job are split off and
+        // handled after the others
+        let mut index = 0;
+        while index < district.peoples.len() {
+            index += 1;
+            let people = &mut district.peoples[index - 1];
+            if people.get_legal_state() != PeopleLegalState::Adult {
+                continue;
+            }
+            let Some(person) = people.as_alive_mut() else {
+                continue;
+            };
+            let Some(home) = person.building_uuid else {
+                continue;
+            };
+
+            let free_jobs = |work: &LayoutId, workers: &HashMap<LayoutId, usize>| {
+                workplaces
+                    .get(work)
+                    .map_or(0, |(jobs, _)| jobs.saturating_sub(workers.get(work).copied().unwrap_or(0)))
+            };
+
+            // the job is lost when the building is gone or has fewer jobs than before
+            if person.workplace_uuid.is_none_or(|w| free_jobs(&w, &workers) == 0) {
+                let jobs = reachable_jobs.entry(home).or_insert_with(|| {
+                    layout
+                        .graph
+                        .get_buildings_connections(home)
+                        .into_iter()
+                        .filter(|b| workplaces.contains_key(b))
+                        .collect()
+                });
+
+                let free: Vec<&LayoutId> = jobs.iter().filter(|j| free_jobs(j, &workers) > 0).collect();
+                person.workplace_uuid = free.choose(rng).map(|j| **j);
+                person.work_status = person.workplace_uuid.map(|w| workplaces[&w].1);
+            }
+
+            let Some(work) = person.workplace_uuid else {
+                continue;
+            };
+            let hired = (person.count as usize).min(free_jobs(&work, &workers)) as u32;
+            let jobless = person.count - hired;
+            if jobless > 0 {
+                let part = people.with_members(jobless, |p| {
+                    let p = p.as_alive_mut().unwrap();
+                    p.workplace_uuid = None;
+                    p.work_status = None;
+                });
+                district.peoples.extend(part);
+            }
+            *workers.entry(work).or_default() += hired as usize;
+
+            let route = routes
+                .entry((home, work))
+                .or_insert_with(|| layout.graph.shortest_path(&home, &work));
+
+            if let Some(route) = route {
+                for id in route.iter() {
+                    *load.entry(*id).or_default() += hired;
+                }
+                commuters.push((district_id, index - 1, home, work));
+            }
+        }
+    }
+
+    let congestion: HashMap<LayoutId, f32> = layout
+        .roads
+        .iter()
+        .map(|r| (r.id, *load.get(&r.id).unwrap_or(&0) as f32 / r.get_class().capacity() as f32))
+        .collect();
+
+    for (district_id, index, home, work) in commuters {
+        let congested = routes[&(home, work)]
+            .iter()
+            .flatten()
+            .any(|id| congestion.get(id).is_some_and(|c| *c > 1.0));
+
+        let district = e.population.get_district_mut(district_id).unwrap();
+        let people = &mut district.peoples[index];
+        let person = people.as_alive_mut().unwrap();
+
+        person.congested_commute = congested;
+        if !congested && (person.mood as i8) >= 0 {
+            continue;
+        }
+        let changing = binomial(rng, person.count, MOOD_CHANGE_CHANCE);
+        let part = people.with_members(changing, |p| {
+            let person = p.as_alive_mut().unwrap();
+            person.mood = if congested {
+                person.mood.worse()
+            } else {
+                person.mood.to_average(Mood::Neutral)
+            };
+        });
+        district.peoples.extend(part);
+    }
+
+    for district_id in 0..e.population.num_districts {
+        e.population.get_district_mut(district_id).unwrap().recalcul_happiness();
+    }
+
+    let congested_roads = congestion.values().filter(|c| **c > 1.0).count();
+    let more_congestion = congested_roads > e.traffic.congested_roads;
+    e.traffic = Traffic { load, congested_roads };
+
+    if more_congestion {
+        send_to_side_bar_auto!(
+            w,
+            &w,
+            format!("Traffic jams on {} roads", congested_roads),
+            LogType::City,
+            LogColor::Unusual
+        );
+    }
+
+    lock_unlock!(w);
+}
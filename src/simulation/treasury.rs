@@ -0,0 +1,176 @@
+use crate::engine::core::LockableEngine;
+use crate::population::district::DistrictZone;
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
+use crate::{lock_write, lock_unlock, send_to_side_bar_auto};
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+
+const STARTING_FUNDS: i64 = 5000;
+/// Share of the build cost of a road paid each month to maintain it
+const ROAD_UPKEEP_SHARE: f32 = 0.01;
+/// Chance each month for someone to get angrier while the city is in debt
+const BANKRUPTCY_MOOD_CHANCE: f64 = 0.1;
+/// Chance each month for someone to get angrier because of high taxes
+const HIGH_TAXES_MOOD_CHANCE: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaxLaw {
+    Low,
+    #[default]
+    Normal,
+    /// More money but people don't like it
+    High,
+}
+
+impl TaxLaw {
+    pub fn income_factor(&self) -> f32 {
+        match self {
+            TaxLaw::Low => 0.5,
+            TaxLaw::Normal => 1.0,
+            TaxLaw::High => 1.5,
+        }
+    }
+
+    /// Low -> Normal -> High -> Low
+    pub fn next(&self) -> TaxLaw {
+        match self {
+            TaxLaw::Low => TaxLaw::Normal,
+            TaxLaw::Normal => TaxLaw::High,
+            TaxLaw::High => TaxLaw::Low,
+        }
+    }
+}
+
+impl Display for TaxLaw {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TaxLaw::Low => write!(f, "low"),
+            TaxLaw::Normal => write!(f, "normal"),
+            TaxLaw::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Tax paid each month by one worker before the tax law
+fn zone_tax(zone: DistrictZone) -> f32 {
+    match zone {
+        DistrictZone::Core => 3.0,
+        DistrictZone::Residentials => 2.0,
+        DistrictZone::Industrials => 2.0,
+        DistrictZone::Slums => 1.0,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Treasury {
+    balance: i64,
+    /// Income minus upkeep of the last month
+    last_delta: i64,
+    pub tax_law: TaxLaw,
+}
+
+impl Default for Treasury {
+    fn default() -> Self {
+        Treasury {
+            balance: STARTING_FUNDS,
+            last_delta: 0,
+            tax_law: TaxLaw::default(),
+        }
+    }
+}
+
+impl Treasury {
+    pub fn get_balance(&self) -> i64 {
+        self.balance
+    }
+
+    pub fn get_last_delta(&self) -> i64 {
+        self.last_delta
+    }
+
+    /// Nothing can be built and people get angry until the debts are paid
+    pub fn is_bankrupt(&self) -> bool {
+        self.balance < 0
+    }
+
+    /// Construction costs, check the balance first
+    pub fn pay(&mut self, amount: u32) {
+        self.balance -= amount as i64;
+    }
+
+    pub fn receive(&mut self, amount: u32) {
+        self.balance += amount as i64;
+    }
+}
+
+/// Will forward one month of taxes \
+/// Every worker pays taxes depending on their district and the tax law, the city pays the
+/// upkeep of its roads and service buildings (see `FunctionRules`). People get angry while
+/// the city is in debt.
+pub fn update_treasury(engine: &LockableEngine, rng: &mut ThreadRng) {
+    lock_write!(engine |> w);
+    let e = &mut *w;
+
+    let mut income = 0.0;
+    for district_id in 0..e.population.num_districts {
+        let district = e.population.get_district(district_id).unwrap();
+        let workers = district
+            .peoples
+            .iter()
+            .filter_map(|p| p.as_alive())
+            .filter(|p| p.workplace_uuid.is_some())
+            .map(|p| p.count as usize)
+            .sum::<usize>();
+        income += workers as f32 * zone_tax(district.zone_type) * e.treasury.tax_law.income_factor();
+    }
+
+    let roads: f32 = e.layout.roads.iter().map(|r| e.layout.road_build_cost(r) as f32 * ROAD_UPKEEP_SHARE).sum();
+    let buildings: u32 = e
+        .layout
+        .buildings
+        .iter()
+        .map(|b| b.get_area().len() as u32 * b.get_function().rules().upkeep)
+        .sum();
+
+    let was_bankrupt = e.treasury.is_bankrupt();
+    let delta = (income - roads).round() as i64 - buildings as i64;
+    e.treasury.balance += delta;
+    e.treasury.last_delta = delta;
+
+    let mood_chance = match (e.treasury.is_bankrupt(), e.treasury.tax_law) {
+        (true, TaxLaw::High) => BANKRUPTCY_MOOD_CHANCE + HIGH_TAXES_MOOD_CHANCE,
+        (true, _) => BANKRUPTCY_MOOD_CHANCE,
+        (false, TaxLaw::High) => HIGH_TAXES_MOOD_CHANCE,
+        (false, _) => 0.0,
+    };
+    if mood_chance > 0.0 {
+        for district_id in 0..e.population.num_districts {
+            let district = e.population.get_district_mut(district_id).unwrap();
+            district.affect_alive(rng, |_| mood_chance, |p| {
+                let person = p.as_alive_mut().unwrap();
+                person.mood = person.mood.worse();
+            });
+            district.recalcul_happiness();
+        }
+    }
+
+    match (was_bankrupt, e.treasury.is_bankrupt()) {
+        (false, true) => {
+            send_to_side_bar_auto!(
+                w,
+                &w,
+                "The city is bankrupt, people are losing faith in the mayor",
+                LogType::City,
+                LogColor::Important
+            );
+        }
+        (true, false) => {
+            send_to_side_bar_auto!(w, &w, "The city paid its debts", LogType::City, LogColor::Unusual);
+        }
+        _ => {}
+    }
+
+    lock_unlock!(w);
+}
@@ -11,11 +11,12 @@ use std::sync::Arc;
 use std::thread::{Scope, ScopedJoinHandle};
 use std::time::Duration;
 use termion::terminal_size;
+use crate::population::people::BasePeopleInfo;
 use crate::population::people::PeopleLegalState::{Child, Elder};
 
 pub fn demo_scope<'scope, 'env>(
     s: &'scope Scope<'scope, 'env>,
-    engine: LockableEngine<'env>,
+    engine: LockableEngine,
     stop_var: Arc<InterruptibleSleep>,
     is_empty : bool
 ) -> ScopedJoinHandle<'scope, ()> {
@@ -58,14 +59,27 @@ pub fn demo_scope<'scope, 'env>(
             );
 
             update_people_in_building(&engine, &mut rng);
+
+            traffic::update_commute(&engine, &mut rng);
+            food::update_food(&engine);
+            diseases::update_diseases(&engine, &mut rng);
+            services::update_services(&engine);
+            treasury::update_treasury(&engine, &mut rng);
+            events::update_events(&engine, &mut rng);
+            monsters::update_monsters(&engine, &mut rng);
     
             
             lock_read!(engine |> pop);
             
             let core_district = pop.population.get_core_district();
 
-            let peoples = core_district.peoples.iter().filter(|p| p.as_alive().is_some()).count();
-            let workers = core_district.peoples.iter().filter(|p| p.get_legal_state() != Child && p.get_legal_state() != Elder).count();
+            let peoples = core_district.count_alive();
+            let workers: usize = core_district
+                .peoples
+                .iter()
+                .filter(|p| p.get_legal_state() != Child && p.get_legal_state() != Elder)
+                .map(|p| p.get_count() as usize)
+                .sum();
 
             let _ = topbar.update_displayed_population(peoples);
 
@@ -73,6 +87,8 @@ pub fn demo_scope<'scope, 'env>(
 
             let _ = topbar.update_displayed_workers(workers as u16, peoples);
 
+            let _ = topbar.update_displayed_treasury(pop.treasury.get_balance(), pop.treasury.get_last_delta());
+
             lock_unlock!(pop);
 
             if is_empty {
@@ -1,9 +1,9 @@
 use crate::engine::core::{Engine, LockableEngine};
-use crate::engine::layout::{BuildingType, LayoutId};
+use crate::engine::layout::{BuildingType, Defense, LayoutId};
 use crate::utils::interruptible_sleep::InterruptibleSleep;
-use crate::{lock_read, lock_unlock, lock_write};
+use crate::{lock_read, lock_unlock, lock_write, send_to_side_bar_auto};
 use std::sync::mpsc::Receiver;
-use std::sync::Arc;
+use std::sync::{Arc, RwLockWriteGuard};
 use std::thread::{Scope, ScopedJoinHandle};
 use log::{debug, trace};
 use serde::Deserialize;
@@ -12,7 +12,10 @@ use crate::engine::drawable::{Drawable, DrawableType};
 use crate::engine::drawable::DrawableType::{Building, BuildingEmpty, Road};
 use crate::engine::keybinds::Clickable;
 use crate::population::Population;
+use crate::roads::road_graph::Graph;
 use crate::threads::engine_loop::SelectionType::Void;
+use crate::threads::sidebar::SideBarMessage;
+use crate::ui::sidebar::{LogColor, LogType};
 use crate::ui::colors::A_UI_WHITE_DARK_COLOR;
 
 #[derive(Copy, Deserialize, Clone, Debug, PartialEq)]
@@ -94,10 +97,152 @@ fn calculate_road_coords(start : (i16,i16), end: (i16,i16)) -> ((i16,i16),(i16,i
     (start, new_end_coords)
 }
 
+/// Warn the player if the last edit cut some buildings from the rest of their road network.
+fn warn_if_split(e: &RwLockWriteGuard<Engine>, before: &Graph) {
+    let cut_off = e.layout.graph.cut_off_buildings(before);
+
+    if cut_off > 0 {
+        send_to_side_bar_auto!(w, e, format!("The road network was split, {} buildings are cut off", cut_off), LogType::City, LogColor::Important);
+    }
+}
+
+fn delete_from_coords(x: i16, y: i16, engine: &LockableEngine) {
+    lock_write!(engine |> e);
+    let before = e.layout.graph.clone();
+    let target = e.get_drawable_for_coordinates(x, y).map(|d| (d.d_type(), d.id()));
+
+    match target {
+        Some((Building, id)) | Some((BuildingEmpty, id)) => {
+            if let Some(removed) = e.layout.remove_building(&id) {
+                let (homeless, witnesses) = e.population.evict_building(&removed.id);
+                send_to_side_bar_auto!(w, &e, format!("Building destroyed, {} people are now homeless", homeless), LogType::City, LogColor::Unusual);
+                for name in witnesses {
+                    send_to_side_bar_auto!(w, &e, format!("{} lost their home", name), LogType::City, LogColor::Important);
+                }
+            }
+        }
+        Some((Road, id)) => {
+            if e.layout.remove_road(&id).is_some() {
+                send_to_side_bar_auto!(w, &e, "Road destroyed", LogType::City, LogColor::Normal);
+            }
+        }
+        _ => return,
+    }
+
+    warn_if_split(&e, &before);
+    e.refresh();
+    lock_unlock!(e);
+}
+
+/// Move the building/road at `target` so its top left corner is at `dest`.
+fn move_from_coords(target: (i16, i16), dest: (i16, i16), engine: &LockableEngine) {
+    lock_write!(engine |> e);
+    let before = e.layout.graph.clone();
+    let budget = e.treasury.get_balance();
+    let moved = match e.get_drawable_for_coordinates(target.0, target.1).map(|d| (d.d_type(), d.id())) {
+        Some((Building, id)) | Some((BuildingEmpty, id)) => e.layout.move_building(&id, dest.0, dest.1, budget),
+        Some((Road, id)) => e.layout.move_road(&id, dest.0, dest.1, budget),
+        _ => return,
+    };
+
+    match moved {
+        Ok(cost) => e.treasury.pay(cost),
+        Err(message) => {
+            send_to_side_bar_auto!(w, &e, message, LogType::Info, LogColor::Unusual);
+        }
+    }
+
+    warn_if_split(&e, &before);
+    e.refresh();
+    lock_unlock!(e);
+}
+
+/// Resize the building/road at `target` so its bottom right corner is at `corner`.
+fn resize_from_coords(target: (i16, i16), corner: (i16, i16), engine: &LockableEngine) {
+    lock_write!(engine |> e);
+    let before = e.layout.graph.clone();
+    let budget = e.treasury.get_balance();
+    let resized = match e.get_drawable_for_coordinates(target.0, target.1).map(|d| (d.d_type(), d.id(), d.x(), d.y())) {
+        Some((Building, id, x, y)) | Some((BuildingEmpty, id, x, y)) => {
+            let (Ok(width), Ok(height)) = (u8::try_from((corner.0 - x).max(0)), u8::try_from((corner.1 - y).max(0))) else {
+                send_to_side_bar_auto!(w, &e, "The building is too big", LogType::Info, LogColor::Unusual);
+                return;
+            };
+            e.layout.resize_building(&id, width, height, budget)
+        }
+        Some((Road, id, x, y)) => {
+            let horizontal = e.layout.roads.iter().find(|r| r.id == id).is_some_and(|r| r.is_horizontal());
+            let Ok(length) = u8::try_from(if horizontal { corner.0 - x } else { corner.1 - y }.max(0)) else {
+                send_to_side_bar_auto!(w, &e, "The road is too long", LogType::Info, LogColor::Unusual);
+                return;
+            };
+            e.layout.resize_road(&id, length, budget)
+        }
+        _ => return,
+    };
+
+    match resized {
+        Ok(cost) => e.treasury.pay(cost),
+        Err(message) => {
+            send_to_side_bar_auto!(w, &e, message, LogType::Info, LogColor::Unusual);
+        }
+    }
+
+    warn_if_split(&e, &before);
+    e.refresh();
+    lock_unlock!(e);
+}
+
+/// Cycle the building at the coordinates between no defense, guard post and wall.
+fn fortify_from_coords(x: i16, y: i16, engine: &LockableEngine) {
+    lock_write!(engine |> e);
+    let Some(id) = e.get_drawable_for_coordinates(x, y).filter(|d| d.d_type() == Building).map(|d| d.id()) else {
+        return;
+    };
+    let Some(current) = e.layout.buildings.iter().find(|b| b.id == id).map(|b| b.get_defense()) else {
+        return;
+    };
+
+    let defense = Defense::next(current);
+    if e.layout.set_defense(&id, defense) {
+        let message = match defense {
+            Some(defense) => format!("The building is now a {}", defense),
+            None => "The building is no longer defended".to_string(),
+        };
+        send_to_side_bar_auto!(w, &e, message, LogType::City, LogColor::Normal);
+    }
+
+    e.refresh();
+    lock_unlock!(e);
+}
+
+/// Give the next function (see `BuildingFunction::next`) to the building at the coordinates.
+fn change_function_from_coords(x: i16, y: i16, engine: &LockableEngine) {
+    lock_write!(engine |> e);
+    let Some(id) = e.get_drawable_for_coordinates(x, y).filter(|d| d.d_type() == Building).map(|d| d.id()) else {
+        return;
+    };
+    let Some(current) = e.layout.buildings.iter().find(|b| b.id == id).map(|b| b.get_function()) else {
+        return;
+    };
+
+    let function = current.next();
+    if e.layout.set_function(&id, function) {
+        send_to_side_bar_auto!(w, &e, format!("The building is now used as {}", function), LogType::City, LogColor::Normal);
+    }
+
+    e.refresh();
+    lock_unlock!(e);
+}
+
+fn is_edit_key(key: Key) -> bool {
+    key == Key::Char('m') || key == Key::Char('r')
+}
+
 
 pub fn engine_loop<'scope, 'env>(
     s: &'scope Scope<'scope, 'env>,
-    engine: LockableEngine<'env>,
+    engine: LockableEngine,
     stop_var: Arc<InterruptibleSleep>,
     click_receiver: Receiver<(i16, i16, (Option<MouseButton>, Option<Key>))>,
     _key_receiver: Receiver<Key>
@@ -108,14 +253,55 @@ pub fn engine_loop<'scope, 'env>(
         fn check_inputs(inputs: &mut Vec<(i16, i16, (Option<MouseButton>, Option<Key>))>, engine: &LockableEngine) {
             let _n = inputs.iter().count();
 
-            if inputs[0].2.0.is_some() && inputs[0].2.0.unwrap() == MouseButton::Left && check_click_target((inputs[0].0, inputs[0].1), engine) == Option::from(BuildingEmpty) {
-                let boolean = replace_building_from_coords(inputs[0].0, inputs[0].1, engine, BuildingType::EmptySpace);
-                if boolean {
+            // Edit tools: left click on something then `x` to delete it, `g` to fortify it, `f` to
+            // change its function or `e` to build on an empty lot,
+            // `m` + left click to move it there or `r` + left click to resize it.
+            if inputs.len() >= 3 && inputs[0].2.0 == Some(MouseButton::Left) && inputs[2].2.0 == Some(MouseButton::Left) {
+                if let Some(key) = inputs[1].2.1.filter(|k| is_edit_key(*k)) {
+                    let (target, dest) = ((inputs[2].0, inputs[2].1), (inputs[0].0, inputs[0].1));
+                    if key == Key::Char('m') {
+                        move_from_coords(target, dest, engine);
+                    } else {
+                        resize_from_coords(target, dest, engine);
+                    }
                     *inputs = vec![];
+                    return;
+                }
+            }
+            if let Some(key) = inputs[0].2.1 {
+                if key == Key::Char('x') || key == Key::Delete {
+                    if inputs.len() >= 2 && inputs[1].2.0 == Some(MouseButton::Left) {
+                        delete_from_coords(inputs[1].0, inputs[1].1, engine);
+                    }
+                    *inputs = vec![];
+                    return;
+                }
+                if key == Key::Char('e') {
+                    if inputs.len() >= 2 && inputs[1].2.0 == Some(MouseButton::Left) && check_click_target((inputs[1].0, inputs[1].1), engine) == Option::from(BuildingEmpty) {
+                        replace_building_from_coords(inputs[1].0, inputs[1].1, engine, BuildingType::EmptySpace);
+                    }
+                    *inputs = vec![];
+                    return;
+                }
+                if key == Key::Char('g') || key == Key::Char('f') {
+                    if inputs.len() >= 2 && inputs[1].2.0 == Some(MouseButton::Left) {
+                        if key == Key::Char('g') {
+                            fortify_from_coords(inputs[1].0, inputs[1].1, engine);
+                        } else {
+                            change_function_from_coords(inputs[1].0, inputs[1].1, engine);
+                        }
+                    }
+                    *inputs = vec![];
+                    return;
+                }
+                if is_edit_key(key) {
+                    if inputs.len() < 2 || inputs[1].2.0 != Some(MouseButton::Left) {
+                        *inputs = vec![];
+                    }
+                    return;
                 }
-
-                return;
             }
+
             if inputs[0].2.1.is_some() && inputs[0].2.1.unwrap() == Key::Esc {
                 *inputs = vec![];
                 lock_write!(engine |> eng);
@@ -174,9 +360,7 @@ pub fn engine_loop<'scope, 'env>(
                         // Cleaned the current selections
                         lock_write!(engine |> eng);
 
-                        let mut i = 0;
-                        eng.layout.roads = eng.layout.roads.clone().into_iter().filter(|r| !r.name.contains("GPS")).collect();
-                        eng.refresh_drawables();
+                        eng.route = vec![];
                         eng.refresh();
                         lock_unlock!(eng);
 
@@ -186,37 +370,19 @@ pub fn engine_loop<'scope, 'env>(
                         let click_1 = get_click_target_id((inputs[0].0, inputs[0].1), engine);
                         let click_2 = get_click_target_id((inputs[1].0, inputs[1].1), engine);
 
-                        //println!("{:?}", inputs);
-
                         if click_type_1 == Option::from(Building) && click_type_2 == Option::from(Building) && click_1.is_some() && click_2.is_some() {
-                            let mut to_highlight = vec![];
                             lock_write!(engine |> eng);
 
-                            let intersections = eng.layout.calculate_path(&click_1.unwrap(), &click_2.unwrap());
-
-                            for (i, window) in intersections.windows(2).enumerate() {
-                                if let [Some(inter), Some(inter2)] = window {
-                                    let hori = !(inter.x == inter2.x || (inter.x - inter2.x).abs() <= 3);
-                                    //println!("GPS{:} horiz : {:}", i, hori);
-                                    to_highlight.push(crate::engine::layout::Road {
-                                        name: format!("GPS{}", i),
-                                        id: LayoutId::random(),
-                                        start_x: if inter.x > inter2.x {inter2.x} else { inter.x },
-                                        start_y: if inter.y > inter2.y {inter2.y} else { inter.y },
-                                        horizontal: if hori {true} else { false },
-                                        width: if hori {inter.height -1 } else { inter.width },
-                                        length: if hori {
-                                            (inter2.x - inter.x).abs() as u8
-                                        } else {
-                                            (inter2.y - inter.y).abs() as u8
-                                        },
-                                        pavement: '#',
-                                    });
+                            match eng.layout.calculate_path(&click_1.unwrap(), &click_2.unwrap()) {
+                                Some(route) => {
+                                    eng.route = route.cells.clone();
+                                    send_to_side_bar_auto!(w, &eng, format!("Route: {:.0} m, about {:.0} s", route.length(), route.travel_time()), LogType::Info, LogColor::Normal);
+                                }
+                                None => {
+                                    send_to_side_bar_auto!(w, &eng, "No road between these buildings", LogType::Info, LogColor::Unusual);
                                 }
                             }
 
-                            to_highlight.iter().for_each(|x| eng.layout.add_road(x.clone()));
-                            eng.refresh_drawables();
                             eng.refresh();
                             lock_unlock!(eng);
                         }
@@ -226,7 +392,7 @@ pub fn engine_loop<'scope, 'env>(
                 if inputs.len() >= 3 && inputs[0].2.1.is_some() && inputs[1].2.0.is_some()  && inputs[2].2.0.is_some() {
                     let mut sel = Option::from(None);
                     lock_write!(engine |> eng);
-                    sel = Option::from((eng.layout.selections[0].clone()));
+                    sel = eng.layout.selections.first().copied();
                     eng.layout.selections = vec![];
                     eng.refresh_drawables();
                     eng.refresh();
@@ -268,7 +434,14 @@ pub fn engine_loop<'scope, 'env>(
                 }
             };
             if let Some(to_del) = to_delete {
-                engine_write.layout.replace_empty_building(to_del);
+                let function = engine_write.build_function;
+                let budget = engine_write.treasury.get_balance();
+                match engine_write.layout.replace_empty_building(to_del, function, budget) {
+                    Ok(cost) => engine_write.treasury.pay(cost),
+                    Err(message) => {
+                        send_to_side_bar_auto!(w, &engine_write, message, LogType::Info, LogColor::Unusual);
+                    }
+                }
                 engine_write.refresh();
                 drop(engine_write);
                 true
@@ -281,13 +454,26 @@ pub fn engine_loop<'scope, 'env>(
 
         pub fn add_building_from_coords(x: i16, y: i16, width: u8, height: u8, engine: &LockableEngine) {
             lock_write!(engine |> e);
-            e.layout.add_building_from_coords(x, y, width, height);
+            let budget = e.treasury.get_balance();
+            let function = e.build_function;
+            match e.layout.add_building_from_coords(x, y, width, height, function, budget) {
+                Ok(cost) => e.treasury.pay(cost),
+                Err(message) => {
+                    send_to_side_bar_auto!(w, &e, message, LogType::Info, LogColor::Unusual);
+                }
+            }
             e.refresh();
         }
 
         pub fn add_road_from_coords(x: i16, y: i16, width: u8, height: u8, engine: &LockableEngine) {
             lock_write!(engine |> e);
-            e.layout.add_road_from_coords(x, y, width, height);
+            let budget = e.treasury.get_balance();
+            match e.layout.add_road_from_coords(x, y, width, height, budget) {
+                Ok(cost) => e.treasury.pay(cost),
+                Err(message) => {
+                    send_to_side_bar_auto!(w, &e, message, LogType::Info, LogColor::Unusual);
+                }
+            }
             e.refresh();
         }
 
@@ -9,6 +9,7 @@ pub const GREY_COLOR: Rgb = Rgb(96, 85, 110);
 pub const GREY_BLUE_COLOR: Rgb = Rgb(59, 64, 94);
 pub const DARK_COLOR: Rgb = Rgb(46, 42, 79);
 pub const DARKEST_COLOR: Rgb = Rgb(33, 29, 56);
+pub const ALERT_COLOR: Rgb = Rgb(226, 69, 52);
 
 // UI
 pub const UI_BLACK_COLOR: Rgb = Rgb(21, 14, 16);
@@ -27,6 +28,7 @@ pub const A_GREY_COLOR: ansi_term::Colour = RGB(96, 85, 110);
 pub const A_GREY_BLUE_COLOR: ansi_term::Colour = RGB(59, 64, 94);
 pub const A_DARK_COLOR: ansi_term::Colour = RGB(46, 42, 79);
 pub const A_DARKEST_COLOR: ansi_term::Colour = RGB(33, 29, 56);
+pub const A_ALERT_COLOR: ansi_term::Colour = RGB(226, 69, 52);
 // GB
 pub const A_UI_BLACK_COLOR: ansi_term::Colour = RGB(21, 14, 16);
 pub const A_UI_BLACK_LIGHT_COLOR: ansi_term::Colour = RGB(39, 39, 57);
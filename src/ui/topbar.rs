@@ -112,6 +112,26 @@ impl TopBar {
         )
     }
 
+    /// Balance on the first line, last month's income minus upkeep under it
+    pub fn update_displayed_treasury(&self, balance: i64, delta: i64) -> Result<(), Error> {
+        draw_text(
+            &self.stdout,
+            &(balance.to_string() + &" ".repeat(4)),
+            56,
+            3,
+            if balance < 0 { ALERT_COLOR } else { UI_WHITE_COLOR },
+            UI_BLACK_COLOR,
+        )?;
+        draw_text(
+            &self.stdout,
+            &(format!("{:+}/month", delta) + &" ".repeat(2)),
+            56,
+            4,
+            UI_WHITE_COLOR,
+            UI_BLACK_COLOR,
+        )
+    }
+
     pub fn draw(&self) -> Result<(), Error> {
         if self.hide {
             return Ok(());
@@ -197,6 +217,24 @@ impl TopBar {
             2,
             3,
             LineStyle::new().direction(LineDirection::Vertical),
+        )?;
+
+        draw_text(
+            &self.stdout,
+            "Treasury :",
+            53,
+            2,
+            UI_WHITE_COLOR,
+            UI_BLACK_COLOR,
+        )?;
+        draw_text(&self.stdout, "0", 56, 3, UI_WHITE_COLOR, UI_BLACK_COLOR)?;
+
+        draw_line(
+            &self.stdout,
+            68,
+            2,
+            3,
+            LineStyle::new().direction(LineDirection::Vertical),
         )
     }
 }
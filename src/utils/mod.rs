@@ -1,6 +1,7 @@
 pub mod interruptible_sleep;
 pub mod pair;
 pub mod intersections;
+pub mod union_find;
 
 use std::sync::{RwLockReadGuard, RwLockWriteGuard};
 use crate::engine::core::{Engine};
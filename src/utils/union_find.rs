@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Disjoint sets with union by size, elements are added on the fly by `union`. \
+/// Elements can't be removed, rebuild the structure instead.
+#[derive(Debug, Clone, Default)]
+pub struct UnionFind<T>
+where T: Copy + Eq + Hash
+{
+    parent: HashMap<T, T>,
+    size: HashMap<T, usize>,
+}
+
+impl<T> UnionFind<T>
+where T: Copy + Eq + Hash
+{
+    pub fn new() -> Self {
+        UnionFind {
+            parent: HashMap::new(),
+            size: HashMap::new(),
+        }
+    }
+
+    /// Representative of the set of `value`, an unknown value is alone in its set. \
+    /// No path compression so it can be used on a shared reference, union by size keeps the trees shallow.
+    pub fn find(&self, value: T) -> T {
+        let mut current = value;
+        while let Some(&parent) = self.parent.get(&current) {
+            if parent == current {
+                break;
+            }
+            current = parent
+        }
+
+        current
+    }
+
+    pub fn union(&mut self, a: T, b: T) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+
+        let size_a = *self.size.get(&root_a).unwrap_or(&1);
+        let size_b = *self.size.get(&root_b).unwrap_or(&1);
+        let (big, small) = if size_a >= size_b { (root_a, root_b) } else { (root_b, root_a) };
+
+        self.parent.insert(small, big);
+        self.parent.entry(big).or_insert(big);
+        self.size.insert(big, size_a + size_b);
+        self.size.remove(&small);
+    }
+
+    pub fn same_set(&self, a: T, b: T) -> bool {
+        self.find(a) == self.find(b)
+    }
+}